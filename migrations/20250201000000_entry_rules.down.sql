@@ -0,0 +1,4 @@
+DROP TABLE entry_tag;
+DROP TABLE tag;
+ALTER TABLE entry DROP COLUMN hidden;
+ALTER TABLE entry DROP COLUMN starred;
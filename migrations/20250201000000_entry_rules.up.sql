@@ -0,0 +1,16 @@
+-- Flags and tags set by rules when new entries are inserted
+ALTER TABLE entry ADD COLUMN starred BOOLEAN DEFAULT FALSE;
+ALTER TABLE entry ADD COLUMN hidden BOOLEAN DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS tag (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name VARCHAR NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS entry_tag (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    tag_id INTEGER NOT NULL,
+    entry_id INTEGER NOT NULL,
+    FOREIGN KEY(tag_id) REFERENCES tag(id) ON DELETE CASCADE,
+    FOREIGN KEY(entry_id) REFERENCES entry(id) ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+ALTER TABLE feed DROP COLUMN notes;
+ALTER TABLE feed DROP COLUMN custom_title;
+ALTER TABLE feed DROP COLUMN url;
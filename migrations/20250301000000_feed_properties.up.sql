@@ -0,0 +1,14 @@
+-- Properties the user can edit, kept apart from what the feed itself reports
+ALTER TABLE feed ADD COLUMN url VARCHAR;
+ALTER TABLE feed ADD COLUMN custom_title VARCHAR;
+ALTER TABLE feed ADD COLUMN notes TEXT;
+
+-- Feeds added before this migration got their subscription link last
+UPDATE feed SET url = (
+    SELECT link.href
+    FROM link
+    JOIN feed_link ON link.id = feed_link.link_id
+    WHERE feed_link.feed_id = feed.id
+    ORDER BY link.id DESC
+    LIMIT 1
+);
@@ -0,0 +1,2 @@
+-- The url a feed was subscribed at before it moved permanently
+ALTER TABLE feed ADD COLUMN redirected_from VARCHAR;
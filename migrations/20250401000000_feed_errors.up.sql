@@ -0,0 +1,2 @@
+-- Why the last update of a feed failed, cleared once it succeeds again
+ALTER TABLE feed ADD COLUMN error VARCHAR;
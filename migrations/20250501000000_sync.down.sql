@@ -0,0 +1,4 @@
+DROP TABLE sync_queue;
+DROP TABLE sync_entry;
+DROP TABLE sync_feed;
+ALTER TABLE feed DROP COLUMN folder;
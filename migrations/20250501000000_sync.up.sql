@@ -0,0 +1,22 @@
+-- Folders come from the sync server, the remote ids tie local rows to the server's
+ALTER TABLE feed ADD COLUMN folder VARCHAR;
+
+CREATE TABLE IF NOT EXISTS sync_feed (
+    feed_id INTEGER PRIMARY KEY NOT NULL,
+    remote_id VARCHAR NOT NULL UNIQUE,
+    FOREIGN KEY(feed_id) REFERENCES feed(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS sync_entry (
+    entry_id INTEGER PRIMARY KEY NOT NULL,
+    remote_id VARCHAR NOT NULL UNIQUE,
+    FOREIGN KEY(entry_id) REFERENCES entry(id) ON DELETE CASCADE
+);
+
+-- Read and star changes waiting to be pushed to the server
+CREATE TABLE IF NOT EXISTS sync_queue (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    entry_id INTEGER NOT NULL,
+    action VARCHAR NOT NULL,
+    FOREIGN KEY(entry_id) REFERENCES entry(id) ON DELETE CASCADE
+);
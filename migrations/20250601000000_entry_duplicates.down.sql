@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS entry_canonical_link;
+DROP INDEX IF EXISTS entry_guid;
+
+ALTER TABLE entry DROP COLUMN canonical_link;
+ALTER TABLE entry DROP COLUMN guid;
@@ -0,0 +1,6 @@
+-- Copies of an entry in other feeds share its GUID or canonical link
+ALTER TABLE entry ADD COLUMN guid VARCHAR;
+ALTER TABLE entry ADD COLUMN canonical_link VARCHAR;
+
+CREATE INDEX IF NOT EXISTS entry_guid ON entry (guid);
+CREATE INDEX IF NOT EXISTS entry_canonical_link ON entry (canonical_link);
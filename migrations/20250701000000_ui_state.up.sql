@@ -0,0 +1,5 @@
+-- The UI state of the last session, a single row of JSON
+CREATE TABLE IF NOT EXISTS ui_state (
+    id INTEGER PRIMARY KEY NOT NULL CHECK (id = 1),
+    state TEXT NOT NULL
+);
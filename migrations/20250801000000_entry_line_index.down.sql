@@ -0,0 +1 @@
+ALTER TABLE entry DROP COLUMN line_index;
@@ -0,0 +1,2 @@
+-- Where reading stopped in each entry
+ALTER TABLE entry ADD COLUMN line_index INTEGER DEFAULT 0;
@@ -0,0 +1,7 @@
+-- Full articles fetched for entries, kept apart from the feed's own content
+CREATE TABLE IF NOT EXISTS entry_article (
+    entry_id INTEGER PRIMARY KEY NOT NULL,
+    content_id INTEGER NOT NULL,
+    FOREIGN KEY(entry_id) REFERENCES entry(id) ON DELETE CASCADE,
+    FOREIGN KEY(content_id) REFERENCES content(id) ON DELETE CASCADE
+);
@@ -0,0 +1 @@
+ALTER TABLE sync_feed DROP COLUMN created;
@@ -0,0 +1,3 @@
+-- Whether the sync created the feed or linked one that was already subscribed.
+-- Feeds synced before this was recorded count as linked, so they are never deleted.
+ALTER TABLE sync_feed ADD COLUMN created BOOLEAN NOT NULL DEFAULT FALSE;
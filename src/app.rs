@@ -1,5 +1,6 @@
 use crate::config::Settings;
 use crate::data::data::{Cache, DataEvent, DataHandler};
+use crate::data::stats::Statistics;
 use crate::error::Error;
 use crate::event::{EventHandler, TerminalEvent};
 use crate::time::Tick;
@@ -12,23 +13,31 @@ use ratatui::layout::Rect;
 use ratatui::prelude::CrosstermBackend;
 use ratatui::Frame;
 use std::io;
+use std::time::{Duration, Instant};
 
-#[derive(Clone, Default, PartialEq, Debug)]
+// How long the work left at exit may take before the app quits anyway
+const SAVE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default, PartialEq, Debug)]
 pub enum RouteId {
     #[default]
     Home,
     Entry,
+    Statistics,
+    Logs,
 }
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone, PartialEq)]
 pub enum ActiveBlock {
     #[default]
     Feeds,
     Entries,
     Entry,
+    Statistics,
+    Logs,
 }
 
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct Route {
     pub id: RouteId,
     pub active_block: ActiveBlock,
@@ -40,12 +49,36 @@ impl Route {
     }
 }
 
+// Where the reader was, saved in the database on exit and restored on the next start
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct UiState {
+    pub routes: Vec<Route>,
+    pub feed_id: Option<i64>,
+    pub entry_id: Option<i64>,
+    pub line_index: u16,
+    pub unread_only: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
 #[derive(Debug)]
 pub enum AppEvent {
     Complete,
     Error(Box<Error>),
+    // Progress of the running event
     DisplayMsg(String),
+    // Something to tell the reader, kept in the message history
+    Notify(Severity, String),
     FeshData(Cache),
+    FullArticle(i64, String),
+    Statistics(Statistics),
+    UiState(UiState),
 }
 
 pub struct App {
@@ -62,7 +95,7 @@ impl App {
             is_running: true,
             is_loading: false,
             ui: Ui::new(config.clone()),
-            data_handler: DataHandler::new(config.database_url.clone()),
+            data_handler: DataHandler::new(config),
             running_data_calls: 0,
         }
     }
@@ -73,6 +106,7 @@ impl App {
         let mut tui = Tui::new(backend, event_handler)?;
 
         self.dispatch(DataEvent::Refresh)?;
+        self.dispatch(DataEvent::LoadUiState)?;
 
         while self.is_running {
             match tui.event_handler.next()? {
@@ -97,10 +131,34 @@ impl App {
             }
         }
         tui.exit()?;
+        if let Some(feed_id) = self.ui.take_deleted_feed() {
+            self.dispatch(DataEvent::DeleteFeed(feed_id))?;
+        }
+        if let Some((entry_id, line_index)) = self.ui.unsaved_position() {
+            self.dispatch(DataEvent::SavePosition(entry_id, line_index))?;
+        }
+        if let Some(state) = self.ui.state() {
+            self.dispatch(DataEvent::SaveUiState(state))?;
+        }
+        self.wait_for_data_calls(SAVE_TIMEOUT);
         self.dispatch(DataEvent::Abort)?;
         Ok(())
     }
 
+    // The data handler stops with the process, so pending work is let finish first
+    fn wait_for_data_calls(&mut self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        while self.running_data_calls > 0 && Instant::now() < deadline {
+            match self.data_handler.next() {
+                Ok(AppEvent::Complete) => {
+                    self.running_data_calls = self.running_data_calls.saturating_sub(1);
+                }
+                Ok(_) => {}
+                Err(_) => std::thread::sleep(Duration::from_millis(10)),
+            }
+        }
+    }
+
     pub fn dispatch(&mut self, event: DataEvent) -> AppResult<()> {
         self.running_data_calls += 1;
         self.is_loading = true;
@@ -130,7 +188,13 @@ impl App {
         }
     }
 
-    pub fn handle_mouse_event(&mut self, _event: MouseEvent) {}
+    pub fn handle_mouse_event(&mut self, event: MouseEvent) {
+        if let Some(callback) = self.ui.handle_mouse_event(event) {
+            if (callback)(self).is_err() {
+                self.is_running = false;
+            }
+        }
+    }
 
     pub fn handle_tick_event(&mut self, _tick: Tick) {
         assert_eq!(self.ui.is_loading, self.is_loading);
@@ -147,9 +211,22 @@ impl App {
                     AppEvent::DisplayMsg(message) => {
                         self.ui.loading_msg = message;
                     }
+                    AppEvent::Notify(severity, message) => {
+                        self.ui.notify(severity, message);
+                    }
                     AppEvent::FeshData(data) => {
                         self.ui.update_feeds(data.feeds);
                         self.ui.update_entries(data.entries);
+                        self.ui.hide_deleted_feed();
+                    }
+                    AppEvent::FullArticle(entry_id, body) => {
+                        self.ui.update_entry_description(entry_id, body);
+                    }
+                    AppEvent::Statistics(stats) => {
+                        self.ui.update_statistics(stats);
+                    }
+                    AppEvent::UiState(state) => {
+                        self.ui.restore_state(state);
                     }
                     AppEvent::Error(_) => {
                         self.is_running = false;
@@ -157,6 +234,12 @@ impl App {
                 }
             }
         }
+
+        if let Some(feed_id) = self.ui.expired_feed() {
+            if self.dispatch(DataEvent::DeleteFeed(feed_id)).is_err() {
+                self.is_running = false;
+            }
+        }
         self.ui.update();
     }
 }
@@ -0,0 +1,141 @@
+// Command line arguments. Without a subcommand crabfeed starts the TUI.
+// `--log-level <level>` goes with any of them.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use log::LevelFilter;
+
+use crate::error::Error;
+use crate::export::ExportFormat;
+use crate::logging::parse_level;
+use crate::AppResult;
+
+pub const USAGE: &str = "\
+Usage:
+    crabfeed
+    crabfeed export [--format markdown|html|epub] [--feed <id|title>] [--entry <id>]... [--output <path>]
+    crabfeed serve [--address <ip:port>]
+    crabfeed import < feed.xml
+
+Options:
+    --log-level <error|warn|info|debug|trace>";
+
+pub const DEFAULT_SERVE_ADDRESS: &str = "127.0.0.1:7878";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Tui,
+    Export(ExportArgs),
+    Serve(SocketAddr),
+    // A feed document read from stdin
+    Import,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportArgs {
+    pub format: ExportFormat,
+    pub feed: Option<String>,
+    pub entries: Vec<i64>,
+    pub output: Option<PathBuf>,
+}
+
+pub fn parse_args<I>(args: I) -> AppResult<Command>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+
+    match args.next().as_deref() {
+        None => Ok(Command::Tui),
+        Some("export") => parse_export(args),
+        Some("serve") => parse_serve(args),
+        Some("import") => match args.next() {
+            None => Ok(Command::Import),
+            Some(arg) => Err(Error::Generic(format!("Unknown argument {arg}\n\n{USAGE}"))),
+        },
+        Some(arg) => Err(Error::Generic(format!("Unknown argument {arg}\n\n{USAGE}"))),
+    }
+}
+
+// Takes `--log-level <level>` out of the arguments, wherever it is
+pub fn take_log_level<I>(args: I) -> AppResult<(Option<LevelFilter>, Vec<String>)>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let mut rest = vec![];
+    let mut level = None;
+
+    while let Some(arg) = args.next() {
+        if arg != "--log-level" {
+            rest.push(arg);
+            continue;
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| Error::Generic(format!("Missing value for {arg}\n\n{USAGE}")))?;
+        level = Some(parse_level(&value)?);
+    }
+
+    Ok((level, rest))
+}
+
+fn parse_export(mut args: impl Iterator<Item = String>) -> AppResult<Command> {
+    let mut export = ExportArgs {
+        format: ExportFormat::Markdown,
+        feed: None,
+        entries: vec![],
+        output: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| Error::Generic(format!("Missing value for {arg}\n\n{USAGE}")))
+        };
+
+        match arg.as_str() {
+            "-f" | "--format" => export.format = value()?.parse()?,
+            "--feed" => export.feed = Some(value()?),
+            "-e" | "--entry" => {
+                let id = value()?;
+                export.entries.push(
+                    id.parse()
+                        .map_err(|_| Error::Generic(format!("Invalid entry id {id}")))?,
+                );
+            }
+            "-o" | "--output" => export.output = Some(PathBuf::from(value()?)),
+            _ => return Err(Error::Generic(format!("Unknown argument {arg}\n\n{USAGE}"))),
+        }
+    }
+
+    if export.feed.is_none() && export.entries.is_empty() {
+        return Err(Error::Generic(format!(
+            "Nothing to export, pass --feed or --entry\n\n{USAGE}"
+        )));
+    }
+
+    Ok(Command::Export(export))
+}
+
+fn parse_serve(mut args: impl Iterator<Item = String>) -> AppResult<Command> {
+    let mut address = DEFAULT_SERVE_ADDRESS.to_string();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-a" | "--address" => {
+                address = args
+                    .next()
+                    .ok_or_else(|| Error::Generic(format!("Missing value for {arg}\n\n{USAGE}")))?
+            }
+            _ => return Err(Error::Generic(format!("Unknown argument {arg}\n\n{USAGE}"))),
+        }
+    }
+
+    address
+        .parse()
+        .map(Command::Serve)
+        .map_err(|_| Error::Generic(format!("Invalid address {address}\n\n{USAGE}")))
+}
@@ -1,6 +1,9 @@
+use std::collections::HashMap;
 use std::fs::create_dir_all;
+use std::str::FromStr;
 
 use directories::BaseDirs;
+use regex::Regex;
 
 use crate::AppResult;
 
@@ -8,6 +11,25 @@ use crate::AppResult;
 pub struct Settings {
     pub colors: ColorSettings,
     pub database_url: String,
+    #[serde(default)]
+    pub feeds: Vec<FeedSettings>,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    #[serde(default)]
+    pub smart_folders: Vec<SmartFolder>,
+    #[serde(default)]
+    pub http: HttpSettings,
+    #[serde(default)]
+    pub sync: Option<SyncSettings>,
+    // Run for every entry an update brings in, see `data::hooks`
+    #[serde(default)]
+    pub hooks: Vec<String>,
+    // Days without a new entry before the statistics list a feed as quiet
+    #[serde(default)]
+    pub stale_days: Option<i64>,
+    // error, warn, info, debug or trace, `--log-level` overrides it
+    #[serde(default)]
+    pub log_level: Option<String>,
 }
 
 impl Default for Settings {
@@ -27,6 +49,14 @@ impl Default for Settings {
                 highlight: "#999999".to_string(),
             },
             database_url: format!("sqlite:/{}/crabfeed/crabfeed.db", dir_str),
+            feeds: vec![],
+            rules: vec![],
+            smart_folders: vec![],
+            http: HttpSettings::default(),
+            sync: None,
+            hooks: vec![],
+            stale_days: None,
+            log_level: None,
         }
     }
 }
@@ -38,6 +68,203 @@ pub struct ColorSettings {
     pub highlight: String,
 }
 
+// Per-feed options, matched against the feed's subscription url
+#[derive(serde::Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct FeedSettings {
+    pub url: String,
+    #[serde(default)]
+    pub full_text: bool,
+    // Overrides the global `http` settings field by field
+    #[serde(default)]
+    pub http: Option<HttpSettings>,
+    #[serde(default)]
+    pub auth: Option<FeedAuth>,
+    // Command the fetched document is piped through before it is parsed, e.g.
+    //   filter: "xmllint --recover -"
+    #[serde(default)]
+    pub filter: Option<String>,
+    // Run for the new entries of this feed only
+    #[serde(default)]
+    pub hooks: Vec<String>,
+}
+
+// Credentials sent with every request for the feed, e.g.
+//   auth:
+//     basic:
+//       username: me
+//       password: { command: "pass show feeds/example" }
+#[derive(serde::Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedAuth {
+    Basic { username: String, password: Secret },
+    Bearer { token: Secret },
+}
+
+// A password or token, written out, read from an environment variable or printed by a
+// command. Only the first line of the command's output is used.
+#[derive(serde::Deserialize, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum Secret {
+    Env { env: String },
+    Command { command: String },
+    Plain(String),
+}
+
+// Keeps secrets out of logs and error messages
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Secret::Env { env } => write!(f, "Secret::Env({env})"),
+            Secret::Command { command } => write!(f, "Secret::Command({command})"),
+            Secret::Plain(_) => write!(f, "Secret::Plain(<redacted>)"),
+        }
+    }
+}
+
+// How feeds and articles are downloaded. Timeouts are in seconds, `read_timeout` is the
+// longest wait for the next part of a body and `total_timeout` is a deadline for the whole
+// request including the body. `proxy` takes http://, https:// and socks5:// urls.
+// Bodies larger than `max_response_size` bytes, after decompression, are rejected.
+#[derive(serde::Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct HttpSettings {
+    pub user_agent: Option<String>,
+    pub connect_timeout: Option<u64>,
+    pub read_timeout: Option<u64>,
+    pub total_timeout: Option<u64>,
+    pub proxy: Option<String>,
+    pub max_response_size: Option<u64>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+impl HttpSettings {
+    // Fields set in `overrides` win, headers from both are sent
+    pub fn merge(&self, overrides: &HttpSettings) -> HttpSettings {
+        let mut headers = self.headers.clone();
+        headers.extend(overrides.headers.clone());
+
+        HttpSettings {
+            user_agent: overrides.user_agent.clone().or(self.user_agent.clone()),
+            connect_timeout: overrides.connect_timeout.or(self.connect_timeout),
+            read_timeout: overrides.read_timeout.or(self.read_timeout),
+            total_timeout: overrides.total_timeout.or(self.total_timeout),
+            proxy: overrides.proxy.clone().or(self.proxy.clone()),
+            max_response_size: overrides.max_response_size.or(self.max_response_size),
+            headers,
+        }
+    }
+}
+
+// A server the subscriptions, entries and their read and starred state are synced with, e.g.
+//   sync:
+//     google_reader:
+//       url: https://rss.example.com/api/greader.php
+//       username: me
+//       password: { env: FRESHRSS_API_PASSWORD }
+// or
+//   sync:
+//     miniflux:
+//       url: https://miniflux.example.com
+//       token: { command: "pass show miniflux" }
+#[derive(serde::Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncSettings {
+    GoogleReader {
+        url: String,
+        username: String,
+        password: Secret,
+    },
+    Miniflux {
+        url: String,
+        token: Secret,
+    },
+}
+
+// A virtual feed listing every entry matched by `query`, see `data::query`
+#[derive(serde::Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct SmartFolder {
+    pub name: String,
+    pub query: String,
+}
+
+// Matched against each new entry as it is inserted. Every condition that is set must match,
+// `title` and `content` are regexes while the rest compare case-insensitively.
+#[derive(serde::Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct Rule {
+    pub feed: Option<String>,
+    pub title: Option<Pattern>,
+    pub content: Option<Pattern>,
+    pub author: Option<String>,
+    pub category: Option<String>,
+    pub actions: Vec<RuleAction>,
+}
+
+// Compiled when the configuration is read, so an invalid regex fails to load instead of
+// never matching
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(try_from = "String")]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    pub fn is_match(&self, text: &str) -> bool {
+        self.0.is_match(text)
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = regex::Error;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        Ok(Pattern(Regex::new(pattern)?))
+    }
+}
+
+impl TryFrom<String> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        pattern.parse()
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+#[derive(serde::Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    MarkRead,
+    Star,
+    Tag(String),
+    Hide,
+    Delete,
+    // Runs the command for the entries the rule matched, e.g. `hook: "notify-send ..."`
+    Hook(String),
+}
+
+impl Settings {
+    pub fn feed_settings(&self, url: &str) -> Option<&FeedSettings> {
+        self.feeds.iter().find(|feed| feed.url == url)
+    }
+
+    // A feed that moved keeps the settings configured for the url it was subscribed with
+    pub fn follow_redirect(&mut self, from: &str, to: &str) {
+        if self.feed_settings(to).is_some() {
+            return;
+        }
+
+        if let Some(settings) = self.feed_settings(from).cloned() {
+            self.feeds.push(FeedSettings {
+                url: to.to_string(),
+                ..settings
+            });
+        }
+    }
+}
+
 impl TryFrom<config::Config> for Settings {
     type Error = config::ConfigError;
 
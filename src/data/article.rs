@@ -0,0 +1,195 @@
+// Downloads an entry's web page and pulls out the main readable content
+
+use super::encoding::decode_text;
+use super::fetch::{content_charset, fetch, read_body, BodyLimits};
+use crate::error::Error;
+use crate::AppResult;
+use html_parser::{Dom, Element, Node};
+use log::debug;
+use reqwest::Client;
+
+const SKIPPED_ELEMENTS: [&str; 12] = [
+    "script", "style", "nav", "header", "footer", "aside", "form", "noscript", "iframe", "svg",
+    "button", "head",
+];
+
+const INLINE_ELEMENTS: [&str; 8] = ["b", "strong", "i", "em", "s", "strike", "a", "u"];
+
+pub async fn fetch_article(client: &Client, url: &str, limits: BodyLimits) -> AppResult<String> {
+    debug!("Fetching full article from {url}...");
+
+    let response = fetch(client, url).await?.response;
+    let charset = content_charset(&response);
+    let html = decode_text(&read_body(response, limits).await?, charset.as_deref());
+
+    extract_article(&html)
+}
+
+// Finds the element holding the most paragraph text and flattens it into
+// the subset of HTML that `ui::util::parse_html` knows how to display
+pub fn extract_article(html: &str) -> AppResult<String> {
+    let dom = Dom::parse(html)?;
+
+    let mut best: Option<(&Element, usize)> = None;
+    let mut nodes: Vec<&Node> = dom.children.iter().collect();
+
+    while let Some(node) = nodes.pop() {
+        let Some(element) = node.element() else {
+            continue;
+        };
+
+        if SKIPPED_ELEMENTS.contains(&element.name.as_str()) {
+            continue;
+        }
+
+        let mut score = paragraph_score(element);
+        if element.name == "article" || element.name == "main" {
+            score += score / 4;
+        }
+
+        if score > best.map(|(_, s)| s).unwrap_or(0) {
+            best = Some((element, score));
+        }
+
+        nodes.extend(element.children.iter());
+    }
+
+    let Some((article, _)) = best else {
+        return Err(Error::Static("No readable content found"));
+    };
+
+    let mut body = String::new();
+    for child in article.children.iter() {
+        write_block(child, &mut body);
+    }
+
+    Ok(body)
+}
+
+fn paragraph_score(element: &Element) -> usize {
+    element
+        .children
+        .iter()
+        .filter_map(|child| child.element())
+        .filter(|child| child.name == "p")
+        .map(text_len)
+        .sum()
+}
+
+fn text_len(element: &Element) -> usize {
+    element
+        .children
+        .iter()
+        .map(|child| match child {
+            Node::Text(text) => text.trim().len(),
+            Node::Element(element) => text_len(element),
+            Node::Comment(_) => 0,
+        })
+        .sum()
+}
+
+fn write_block(node: &Node, out: &mut String) {
+    let element = match node {
+        Node::Text(text) => {
+            if !text.trim().is_empty() {
+                out.push_str(&format!("<p>{}</p>", text.trim()));
+            }
+            return;
+        }
+        Node::Element(element) => element,
+        Node::Comment(_) => return,
+    };
+
+    let name = element.name.as_str();
+
+    if SKIPPED_ELEMENTS.contains(&name) {
+        return;
+    }
+
+    match name {
+        "p" | "h1" | "h2" | "h3" | "h4" | "h5" => {
+            let mut inner = String::new();
+            for child in element.children.iter() {
+                write_inline(child, &mut inner);
+            }
+            if !inner.trim().is_empty() {
+                out.push_str(&format!("<{name}>{inner}</{name}>"));
+            }
+        }
+        "h6" | "blockquote" | "pre" | "figcaption" => {
+            let mut inner = String::new();
+            for child in element.children.iter() {
+                write_inline(child, &mut inner);
+            }
+            if !inner.trim().is_empty() {
+                out.push_str(&format!("<p>{inner}</p>"));
+            }
+        }
+        "ul" | "ol" => {
+            out.push_str("<ul>");
+            for item in element.children.iter().filter_map(|child| child.element()) {
+                if item.name != "li" {
+                    continue;
+                }
+                let mut inner = String::new();
+                for child in item.children.iter() {
+                    write_inline(child, &mut inner);
+                }
+                out.push_str(&format!("<li>{inner}</li>"));
+            }
+            out.push_str("</ul>");
+        }
+        "br" => out.push_str("<br>"),
+        _ if INLINE_ELEMENTS.contains(&name) => {
+            let mut inner = String::new();
+            write_inline(node, &mut inner);
+            out.push_str(&format!("<p>{inner}</p>"));
+        }
+        _ => {
+            for child in element.children.iter() {
+                write_block(child, out);
+            }
+        }
+    }
+}
+
+fn write_inline(node: &Node, out: &mut String) {
+    match node {
+        Node::Text(text) => out.push_str(text),
+        Node::Element(element) => {
+            let name = element.name.as_str();
+
+            if SKIPPED_ELEMENTS.contains(&name) {
+                return;
+            }
+
+            if INLINE_ELEMENTS.contains(&name) {
+                if name == "a" {
+                    let href = element
+                        .attributes
+                        .get("href")
+                        .cloned()
+                        .flatten()
+                        .unwrap_or_default();
+                    out.push_str(&format!("<a href=\"{href}\">"));
+                } else {
+                    out.push_str(&format!("<{name}>"));
+                }
+
+                for child in element.children.iter() {
+                    write_inline(child, out);
+                }
+
+                out.push_str(&format!("</{name}>"));
+            } else {
+                for child in element.children.iter() {
+                    write_inline(child, out);
+                }
+                if name == "br" {
+                    out.push(' ');
+                }
+            }
+        }
+        Node::Comment(_) => {}
+    }
+}
@@ -1,20 +1,35 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
 use std::process::exit;
 
-use crate::app::AppEvent;
+use crate::app::{AppEvent, Severity, UiState};
+use crate::config::{Settings, SyncSettings};
 use crate::error::Error;
-use crate::prelude::{Entry, EntryData, FeedData};
+use crate::export::{self, default_export_path, ExportEntry, ExportFormat};
+use crate::prelude::{Entry, EntryData, FeedData, FeedProperties, InsertedEntry};
 use crate::AppResult;
 
+use super::article::fetch_article;
 use super::db::{
-    self, connect, insert_feed, insert_link, mark_entry_read, select_all_entries,
-    select_all_entry_links, select_all_feed_links, select_all_feeds, select_content, select_feed,
-    select_media, update_feed_title,
+    self, connect, insert_article, insert_feed, insert_link, mark_entry_read, mark_entry_starred,
+    select_all_entries, select_all_entry_authors, select_all_entry_links, select_all_entry_tags,
+    select_all_feed_links, select_all_feeds, select_article, select_content, select_duplicate_ids,
+    select_entry, select_feed, select_media, select_query_entries, select_sync_feeds,
+    select_ui_state, update_entry_line_index, update_feed_error, update_feed_properties,
+    update_feed_redirect, update_feed_title, update_feed_url, update_ui_state,
 };
+use super::duplicates::collapse_duplicates;
+use super::encoding::decode_feed;
+use super::fetch::{build_sync_client, HttpClients};
+use super::hooks::{prepare_hooks, run_hooks};
+use super::query::EntryQuery;
+use super::source::download;
+use super::stats::{statistics, DEFAULT_STALE_DAYS};
+use super::sync::{queue_change, sync, SyncAction, SyncBackend};
+use chrono::Utc;
 use feed_rs::parser;
 use log::debug;
-use reqwest;
 use sqlx::SqliteConnection;
-use tokio::time::{sleep, Duration};
 
 #[derive(Debug)]
 pub struct Cache {
@@ -29,8 +44,17 @@ pub enum DataEvent {
     UpdateFeeds,
     AddFeed(String),
     DeleteFeed(i64),
+    EditFeed(i64, FeedProperties),
     Refresh,
     ReadEntry(i64),
+    ReadEntries(Vec<i64>),
+    SavePosition(i64, u16),
+    FetchArticle(i64),
+    StarEntry(i64, bool),
+    Export(Vec<i64>, ExportFormat, String),
+    Statistics,
+    LoadUiState,
+    SaveUiState(UiState),
     Abort,
 }
 
@@ -41,7 +65,7 @@ pub struct DataHandler {
 }
 
 impl DataHandler {
-    pub fn new(database_url: String) -> Self {
+    pub fn new(config: Settings) -> Self {
         debug!("Creating Data channels");
         let (sync_sender, sync_receiver) = std::sync::mpsc::channel();
         let (async_sender, async_receiver) = tokio::sync::mpsc::channel(32);
@@ -57,7 +81,7 @@ impl DataHandler {
                             exit(0);
                         }
                         _ => {
-                            handle_event(database_url.clone(), event, moved_sender.clone())
+                            handle_event(&config, event, moved_sender.clone())
                                 .await
                                 .expect("Failed to handle Event");
                         }
@@ -89,27 +113,55 @@ impl DataHandler {
 
 // Handle Event
 pub async fn handle_event(
-    database_url: String,
+    config: &Settings,
     event: DataEvent,
     sender: tokio::sync::mpsc::Sender<AppEvent>,
 ) -> AppResult<()> {
     debug!("Handling Data Event...");
+    let database_url = config.database_url.clone();
     match event {
         DataEvent::UpdateFeeds => {
-            update_feeds(database_url, sender.clone()).await?;
+            update_feeds(database_url, config, sender.clone()).await?;
         }
         DataEvent::AddFeed(url) => {
-            add_feed(database_url, url, sender.clone()).await?;
+            add_feed(database_url, config, url, sender.clone()).await?;
         }
         DataEvent::DeleteFeed(id) => {
             delete_feed(database_url, sender.clone(), id).await?;
         }
+        DataEvent::EditFeed(id, properties) => {
+            edit_feed(database_url, id, properties, sender.clone()).await?;
+        }
         DataEvent::Refresh => {
-            refresh(database_url, sender.clone()).await?;
+            refresh(database_url, config, sender.clone()).await?;
         }
         DataEvent::ReadEntry(entry_id) => {
             read_entry(database_url, &entry_id, sender.clone()).await?;
         }
+        DataEvent::SavePosition(entry_id, line_index) => {
+            save_position(database_url, &entry_id, line_index, sender.clone()).await?;
+        }
+        DataEvent::ReadEntries(entry_ids) => {
+            read_entries(database_url, &entry_ids, sender.clone()).await?;
+        }
+        DataEvent::StarEntry(entry_id, starred) => {
+            star_entry(database_url, &entry_id, starred, sender.clone()).await?;
+        }
+        DataEvent::Export(entry_ids, format, title) => {
+            export_to_file(database_url, entry_ids, format, title, sender.clone()).await?;
+        }
+        DataEvent::FetchArticle(entry_id) => {
+            fetch_full_article(database_url, config, &entry_id, sender.clone()).await?;
+        }
+        DataEvent::Statistics => {
+            load_statistics(database_url, config, sender.clone()).await?;
+        }
+        DataEvent::LoadUiState => {
+            load_ui_state(database_url, sender.clone()).await?;
+        }
+        DataEvent::SaveUiState(state) => {
+            save_ui_state(database_url, &state, sender.clone()).await?;
+        }
         _ => {}
     }
 
@@ -119,51 +171,162 @@ pub async fn handle_event(
 // Fetch feeds and update the app state
 async fn update_feeds(
     database_url: String,
+    config: &Settings,
     sender: tokio::sync::mpsc::Sender<AppEvent>,
 ) -> AppResult<()> {
     debug!("Updating Feeds...");
 
     let conn = &mut connect(database_url).await?;
+    let mut config = follow_redirects(conn, config).await?;
+    let clients = match HttpClients::new(&config) {
+        Ok(clients) => clients,
+        Err(e) => return show_failure(&sender, format!("Invalid http settings: {e}")).await,
+    };
+
+    let mut inserted = vec![];
+
+    // Feeds on the sync server come from there, only the rest is fetched directly
+    if let Some(settings) = &config.sync {
+        inserted.extend(sync_with_server(conn, &config, settings, &sender).await?);
+    }
+
+    let mut feed_items = select_all_feeds(conn).await?;
 
-    let feed_items = select_all_feeds(conn).await?;
+    if config.sync.is_some() {
+        let synced: HashSet<i64> = select_sync_feeds(conn)
+            .await?
+            .into_iter()
+            .map(|feed| feed.local_id)
+            .collect();
+        feed_items.retain(|feed| !synced.contains(&feed.id));
+    }
 
     let mut new_feeds = vec![];
+    let mut full_text_feeds = vec![];
 
     for feed in feed_items.iter() {
         sender
             .send(AppEvent::DisplayMsg(format!(
                 "Updating {}...",
-                feed.title.clone().unwrap_or("Untitled Feed".to_string())
+                feed.display_title().unwrap_or("Untitled Feed".to_string())
             )))
             .await
             .expect("Failed to send DataEvent::Updating");
 
-        let links = select_all_feed_links(conn, &feed.id).await?;
-
-        for link in links.iter() {
-            if let Ok(res) = reqwest::get(link.href.clone()).await {
-                if let Ok(content) = res.text().await {
-                    let new_feed = parser::parse(content.as_bytes());
-
-                    if let Ok(neofeed) = new_feed {
-                        if let Some(new_title) = &neofeed.title {
-                            if let Some(old_title) = &feed.title {
-                                if new_title.content != *old_title {
-                                    update_feed_title(conn, &feed.id, new_title.content.clone())
-                                        .await?;
-                                }
-                            }
-                        }
-                        new_feeds.push(neofeed);
-                    };
+        // Feeds without a subscription url predate it, any of their links may be the feed
+        let urls = match &feed.url {
+            Some(url) => vec![url.clone()],
+            None => select_all_feed_links(conn, &feed.id)
+                .await?
+                .into_iter()
+                .map(|link| link.href)
+                .collect(),
+        };
+
+        if urls.iter().any(|url| {
+            config
+                .feed_settings(url)
+                .is_some_and(|settings| settings.full_text)
+        }) {
+            full_text_feeds.push(feed.clone());
+        }
+
+        for url in urls.iter() {
+            let is_subscription = feed.url.as_ref() == Some(url);
+
+            let fetched = match download(&config, &clients, url).await {
+                Ok(fetched) => fetched,
+                Err(e) => {
+                    debug!("Could not fetch {url}: {e}");
+
+                    if is_subscription {
+                        sender
+                            .send(AppEvent::Notify(
+                                Severity::Warning,
+                                format!(
+                                    "{}: {e}",
+                                    feed.display_title().unwrap_or("Untitled Feed".to_string())
+                                ),
+                            ))
+                            .await
+                            .expect("Failed to send AppEvent::Notify");
+
+                        update_feed_error(conn, &feed.id, Some(e.to_string())).await?;
+                    }
+                    continue;
+                }
+            };
+
+            if is_subscription && feed.error.is_some() {
+                update_feed_error(conn, &feed.id, None).await?;
+            }
+
+            if let Some(moved_to) = &fetched.moved_to {
+                if is_subscription {
+                    sender
+                        .send(AppEvent::Notify(
+                            Severity::Info,
+                            format!(
+                                "{} moved to {moved_to}",
+                                feed.display_title().unwrap_or("Untitled Feed".to_string())
+                            ),
+                        ))
+                        .await
+                        .expect("Failed to send AppEvent::Notify");
+
+                    update_feed_redirect(conn, &feed.id, url.clone(), moved_to.clone()).await?;
+                    config.follow_redirect(url, moved_to);
                 }
             }
+
+            let new_feed = parser::parse(fetched.body.as_slice());
+
+            if let Ok(neofeed) = new_feed {
+                if let Some(new_title) = &neofeed.title {
+                    if let Some(old_title) = &feed.title {
+                        if new_title.content != *old_title {
+                            update_feed_title(conn, &feed.id, new_title.content.clone()).await?;
+                        }
+                    }
+                }
+                new_feeds.push(neofeed);
+            };
         }
     }
 
     //Update the database
     for feed in new_feeds {
-        insert_feed(conn, feed).await?;
+        let (_, new_entries) = insert_feed(conn, feed, &config.rules).await?;
+        inserted.extend(new_entries);
+    }
+
+    // Hooks don't hold up the update, their failures are only logged
+    match prepare_hooks(conn, &config, &inserted).await {
+        Ok(hooks) if !hooks.is_empty() => {
+            tokio::spawn(run_hooks(hooks));
+        }
+        Ok(_) => {}
+        Err(e) => debug!("Could not prepare hooks: {e}"),
+    }
+
+    for feed in full_text_feeds.iter() {
+        sender
+            .send(AppEvent::DisplayMsg(format!(
+                "Fetching articles for {}...",
+                feed.display_title().unwrap_or("Untitled Feed".to_string())
+            )))
+            .await
+            .expect("Failed to send AppEvent::DisplayMsg");
+
+        for entry in select_all_entries(conn, &feed.id).await? {
+            if entry.read.unwrap_or_default() || select_article(conn, &entry.id).await?.is_some() {
+                continue;
+            }
+
+            if let Err(e) = cache_article(conn, &clients, &entry.id).await {
+                debug!("Could not fetch article for entry {}: {e}", entry.id);
+            }
+        }
     }
 
     sender
@@ -176,30 +339,23 @@ async fn update_feeds(
 
 async fn add_feed(
     database_url: String,
+    config: &Settings,
     feed_url: String,
     sender: tokio::sync::mpsc::Sender<AppEvent>,
 ) -> AppResult<()> {
     debug!("Adding {feed_url}...");
 
-    let Ok(response) = reqwest::get(feed_url.as_str()).await else {
-        sender
-            .send(AppEvent::DisplayMsg("Could not find feed".to_string()))
-            .await
-            .expect("Failed to send AppEvent::DisplayMsg");
-
-        sleep(Duration::from_secs(1)).await;
-
-        sender
-            .send(AppEvent::Complete)
-            .await
-            .expect("Failed to send AppEvent::Complete");
-
-        return Ok(());
+    let clients = match HttpClients::new(config) {
+        Ok(clients) => clients,
+        Err(e) => return show_failure(&sender, format!("Invalid http settings: {e}")).await,
     };
 
-    let content = response.text().await.expect("Failed to get feed data");
+    let fetched = match download(config, &clients, &feed_url).await {
+        Ok(fetched) => fetched,
+        Err(e) => return show_failure(&sender, format!("Could not add feed: {e}")).await,
+    };
 
-    let feed = parser::parse(content.as_bytes());
+    let feed = parser::parse(fetched.body.as_slice());
 
     if let Ok(feed) = feed {
         if feed.title.is_none() {
@@ -220,11 +376,16 @@ async fn add_feed(
         let conn = &mut connect(database_url)
             .await
             .expect("Failed to connect to Database");
-        let feed_id = insert_feed(conn, feed).await?;
+        let (feed_id, _) = insert_feed(conn, feed, &config.rules).await?;
 
-        insert_link(conn, feed_url, Some(feed_id), None)
+        insert_link(conn, feed_url.clone(), Some(feed_id), None)
             .await
             .expect("Failed to insert link");
+        // Subscribe to where the feed lives now if it has moved for good
+        match fetched.moved_to {
+            Some(moved_to) => update_feed_redirect(conn, &feed_id, feed_url, moved_to).await?,
+            None => update_feed_url(conn, &feed_id, feed_url).await?,
+        }
     }
 
     sender
@@ -248,7 +409,7 @@ async fn delete_feed(
     sender
         .send(AppEvent::DisplayMsg(format!(
             "Deleting {}...",
-            feed.title.unwrap_or("Untitled Feed".to_string())
+            feed.display_title().unwrap_or("Untitled Feed".to_string())
         )))
         .await
         .expect("Failed to send Deleting event");
@@ -263,8 +424,27 @@ async fn delete_feed(
     Ok(())
 }
 
+async fn edit_feed(
+    database_url: String,
+    feed_id: i64,
+    properties: FeedProperties,
+    sender: tokio::sync::mpsc::Sender<AppEvent>,
+) -> AppResult<()> {
+    let conn = &mut connect(database_url).await?;
+
+    update_feed_properties(conn, &feed_id, &properties).await?;
+
+    sender
+        .send(AppEvent::Complete)
+        .await
+        .expect("Failed to send AppEvent::Complete");
+
+    Ok(())
+}
+
 async fn refresh(
     database_url: String,
+    config: &Settings,
     sender: tokio::sync::mpsc::Sender<AppEvent>,
 ) -> AppResult<()> {
     debug!("Refreshing data...");
@@ -281,12 +461,14 @@ async fn refresh(
     for feed in feeds {
         let mut data = FeedData::from(feed.clone());
 
-        if let Some(link) = select_all_feed_links(conn, &feed.id)
-            .await
-            .expect("Failed to connect to Database")
-            .first()
-        {
-            data.update_url(link.href.clone());
+        if data.url.is_empty() {
+            if let Some(link) = select_all_feed_links(conn, &feed.id)
+                .await
+                .expect("Failed to connect to Database")
+                .first()
+            {
+                data.update_url(link.href.clone());
+            }
         }
 
         feed_data.push(data);
@@ -300,18 +482,47 @@ async fn refresh(
         let mut entry_data = vec![];
 
         for entry in entries {
-            let mut data = EntryData::from(entry.clone());
+            if entry.hidden.unwrap_or_default() {
+                continue;
+            }
+
+            entry_data.push(load_entry_data(conn, &entry).await?);
+        }
+
+        entry_groups.push(entry_data);
+    }
+
+    for (index, folder) in config.smart_folders.iter().enumerate() {
+        // Smart folders aren't stored, so they take negative ids to stay apart from feeds
+        let data = FeedData {
+            id: -(index as i64 + 1),
+            title: folder.name.clone(),
+            query: Some(folder.query.clone()),
+            ..Default::default()
+        };
 
-            data.description = process_entry_description(conn, &entry).await?;
+        let mut entry_data = vec![];
 
-            let links = select_all_entry_links(conn, &entry.id).await?;
-            data.update_links(links);
-            entry_data.push(data);
+        match folder.query.parse::<EntryQuery>() {
+            Ok(entry_query) => {
+                let entries = select_query_entries(conn, &entry_query).await?;
+                for entry in collapse_duplicates(entries) {
+                    entry_data.push(load_entry_data(conn, &entry).await?);
+                }
+            }
+            Err(e) => {
+                debug!("Invalid query for {}: {e}", folder.name);
+            }
         }
 
+        feed_data.push(data);
         entry_groups.push(entry_data);
     }
 
+    for (data, entries) in feed_data.iter_mut().zip(entry_groups.iter()) {
+        data.update_unread(entries.iter().filter(|entry| !entry.read).count());
+    }
+
     sender
         .send(AppEvent::FeshData(Cache {
             feeds: feed_data,
@@ -328,6 +539,22 @@ async fn refresh(
     Ok(())
 }
 
+async fn load_entry_data(conn: &mut SqliteConnection, entry: &Entry) -> AppResult<EntryData> {
+    let mut data = EntryData::from(entry.clone());
+
+    data.description = process_entry_description(conn, entry).await?;
+
+    let links = select_all_entry_links(conn, &entry.id).await?;
+    data.update_links(links);
+
+    let tags = select_all_entry_tags(conn, &entry.id).await?;
+    data.update_tags(tags);
+
+    data.duplicates = select_duplicate_ids(conn, &entry.id).await?;
+
+    Ok(data)
+}
+
 async fn read_entry(
     database_url: String,
     entry_id: &i64,
@@ -335,7 +562,288 @@ async fn read_entry(
 ) -> AppResult<()> {
     let conn = &mut connect(database_url).await?;
 
-    mark_entry_read(conn, entry_id).await?;
+    mark_read_with_duplicates(conn, entry_id).await?;
+
+    sender
+        .send(AppEvent::Complete)
+        .await
+        .expect("Failed to send AppEvent::Complete");
+
+    Ok(())
+}
+
+async fn save_position(
+    database_url: String,
+    entry_id: &i64,
+    line_index: u16,
+    sender: tokio::sync::mpsc::Sender<AppEvent>,
+) -> AppResult<()> {
+    let conn = &mut connect(database_url).await?;
+
+    update_entry_line_index(conn, entry_id, line_index as i64).await?;
+
+    sender
+        .send(AppEvent::Complete)
+        .await
+        .expect("Failed to send AppEvent::Complete");
+
+    Ok(())
+}
+
+// Reading one copy of an article reads the copies other feeds published too
+async fn mark_read_with_duplicates(conn: &mut SqliteConnection, entry_id: &i64) -> AppResult<()> {
+    let mut entry_ids = vec![*entry_id];
+    entry_ids.extend(select_duplicate_ids(conn, entry_id).await?);
+
+    for entry_id in entry_ids.iter() {
+        mark_entry_read(conn, entry_id).await?;
+        queue_change(conn, entry_id, SyncAction::Read).await?;
+    }
+
+    Ok(())
+}
+
+async fn read_entries(
+    database_url: String,
+    entry_ids: &[i64],
+    sender: tokio::sync::mpsc::Sender<AppEvent>,
+) -> AppResult<()> {
+    let conn = &mut connect(database_url).await?;
+
+    for entry_id in entry_ids {
+        mark_read_with_duplicates(conn, entry_id).await?;
+    }
+
+    sender
+        .send(AppEvent::Complete)
+        .await
+        .expect("Failed to send AppEvent::Complete");
+
+    Ok(())
+}
+
+async fn star_entry(
+    database_url: String,
+    entry_id: &i64,
+    starred: bool,
+    sender: tokio::sync::mpsc::Sender<AppEvent>,
+) -> AppResult<()> {
+    let conn = &mut connect(database_url).await?;
+
+    mark_entry_starred(conn, entry_id, starred).await?;
+    let action = match starred {
+        true => SyncAction::Star,
+        false => SyncAction::Unstar,
+    };
+    queue_change(conn, entry_id, action).await?;
+
+    sender
+        .send(AppEvent::Complete)
+        .await
+        .expect("Failed to send AppEvent::Complete");
+
+    Ok(())
+}
+
+// Pushes the queued changes and pulls everything new from the sync server, returning
+// the new entries. A server that can't be reached leaves the database as it is, to be
+// read offline.
+async fn sync_with_server(
+    conn: &mut SqliteConnection,
+    config: &Settings,
+    settings: &SyncSettings,
+    sender: &tokio::sync::mpsc::Sender<AppEvent>,
+) -> AppResult<Vec<InsertedEntry>> {
+    sender
+        .send(AppEvent::DisplayMsg("Syncing...".to_string()))
+        .await
+        .expect("Failed to send AppEvent::DisplayMsg");
+
+    let result = match build_sync_client(&config.http) {
+        Ok(client) => match SyncBackend::connect(settings, client).await {
+            Ok(mut backend) => sync(conn, &mut backend, &config.rules).await,
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(report) => {
+            debug!("Sync finished: {report:?}");
+            Ok(report.new_entries)
+        }
+        Err(e) => {
+            debug!("Sync failed: {e}");
+            sender
+                .send(AppEvent::Notify(
+                    Severity::Warning,
+                    format!("Sync failed, changes stay queued: {e}"),
+                ))
+                .await
+                .expect("Failed to send AppEvent::Notify");
+
+            Ok(vec![])
+        }
+    }
+}
+
+async fn fetch_full_article(
+    database_url: String,
+    config: &Settings,
+    entry_id: &i64,
+    sender: tokio::sync::mpsc::Sender<AppEvent>,
+) -> AppResult<()> {
+    let conn = &mut connect(database_url).await?;
+
+    sender
+        .send(AppEvent::DisplayMsg("Fetching full article...".to_string()))
+        .await
+        .expect("Failed to send AppEvent::DisplayMsg");
+
+    let article = match select_article(conn, entry_id).await? {
+        Some(content) => Ok(content.body.unwrap_or_default()),
+        None => match HttpClients::new(&follow_redirects(conn, config).await?) {
+            Ok(clients) => cache_article(conn, &clients, entry_id).await,
+            Err(e) => Err(e),
+        },
+    };
+
+    match article {
+        Ok(body) => {
+            sender
+                .send(AppEvent::FullArticle(*entry_id, body))
+                .await
+                .expect("Failed to send AppEvent::FullArticle");
+        }
+        Err(e) => {
+            debug!("Could not fetch article for entry {entry_id}: {e}");
+            sender
+                .send(AppEvent::Notify(
+                    Severity::Error,
+                    format!("Could not fetch article: {e}"),
+                ))
+                .await
+                .expect("Failed to send AppEvent::Notify");
+        }
+    }
+
+    sender
+        .send(AppEvent::Complete)
+        .await
+        .expect("Failed to send AppEvent::Complete");
+
+    Ok(())
+}
+
+// Downloads the page behind the entry's first link and stores its readable content.
+// The page is fetched with the http settings of the entry's feed.
+async fn cache_article(
+    conn: &mut SqliteConnection,
+    clients: &HttpClients,
+    entry_id: &i64,
+) -> AppResult<String> {
+    let links = select_all_entry_links(conn, entry_id).await?;
+
+    let Some(link) = links
+        .iter()
+        .find(|link| link.rel.is_none() || link.rel.as_deref() == Some("alternate"))
+    else {
+        return Err(Error::Static("Entry has no article link"));
+    };
+
+    let entry = select_entry(conn, entry_id).await?;
+    let feed = select_feed(conn, &entry.feed_id).await?;
+    let (client, limits) = match &feed.url {
+        Some(url) => (clients.client(url), clients.limits(url)),
+        None => (clients.default_client(), clients.default_limits()),
+    };
+
+    let body = fetch_article(client, &link.href, limits).await?;
+    insert_article(conn, entry_id, link.href.clone(), body.clone()).await?;
+
+    Ok(body)
+}
+
+// The configuration with the settings of moved feeds following them to their new url
+async fn follow_redirects(conn: &mut SqliteConnection, config: &Settings) -> AppResult<Settings> {
+    let mut config = config.clone();
+
+    for feed in select_all_feeds(conn).await? {
+        if let (Some(from), Some(to)) = (&feed.redirected_from, &feed.url) {
+            config.follow_redirect(from, to);
+        }
+    }
+
+    Ok(config)
+}
+
+// Tells why an event failed, then finishes it
+async fn show_failure(
+    sender: &tokio::sync::mpsc::Sender<AppEvent>,
+    message: String,
+) -> AppResult<()> {
+    sender
+        .send(AppEvent::Notify(Severity::Error, message))
+        .await
+        .expect("Failed to send AppEvent::Notify");
+
+    sender
+        .send(AppEvent::Complete)
+        .await
+        .expect("Failed to send AppEvent::Complete");
+
+    Ok(())
+}
+
+async fn export_to_file(
+    database_url: String,
+    entry_ids: Vec<i64>,
+    format: ExportFormat,
+    title: String,
+    sender: tokio::sync::mpsc::Sender<AppEvent>,
+) -> AppResult<()> {
+    sender
+        .send(AppEvent::DisplayMsg(format!("Exporting {title}...")))
+        .await
+        .expect("Failed to send AppEvent::DisplayMsg");
+
+    let (severity, message) =
+        match export_entries(database_url, &entry_ids, format, &title, None).await {
+            Ok(path) => (Severity::Info, format!("Exported to {}", path.display())),
+            Err(e) => {
+                debug!("Export failed: {e}");
+                (Severity::Error, format!("Export failed: {e}"))
+            }
+        };
+
+    sender
+        .send(AppEvent::Notify(severity, message))
+        .await
+        .expect("Failed to send AppEvent::Notify");
+
+    sender
+        .send(AppEvent::Complete)
+        .await
+        .expect("Failed to send AppEvent::Complete");
+
+    Ok(())
+}
+
+async fn load_statistics(
+    database_url: String,
+    config: &Settings,
+    sender: tokio::sync::mpsc::Sender<AppEvent>,
+) -> AppResult<()> {
+    let conn = &mut connect(database_url).await?;
+
+    let stale_days = config.stale_days.unwrap_or(DEFAULT_STALE_DAYS);
+    match statistics(conn, Utc::now().naive_utc(), stale_days).await {
+        Ok(stats) => sender
+            .send(AppEvent::Statistics(stats))
+            .await
+            .expect("Failed to send AppEvent::Statistics"),
+        Err(e) => return show_failure(&sender, format!("Could not load statistics: {e}")).await,
+    }
 
     sender
         .send(AppEvent::Complete)
@@ -345,10 +853,139 @@ async fn read_entry(
     Ok(())
 }
 
+// Shared by the TUI and `crabfeed export`, returns where the file was written
+pub async fn export_entries(
+    database_url: String,
+    entry_ids: &[i64],
+    format: ExportFormat,
+    title: &str,
+    output: Option<PathBuf>,
+) -> AppResult<PathBuf> {
+    let conn = &mut connect(database_url).await?;
+    let mut entries = vec![];
+
+    for entry_id in entry_ids {
+        let entry = select_entry(conn, entry_id).await?;
+        entries.push(load_export_entry(conn, &entry).await?);
+    }
+
+    if entries.is_empty() {
+        return Err(Error::Static("No entries to export"));
+    }
+
+    let path = output.unwrap_or_else(|| default_export_path(title, format));
+    export::export(&path, format, title, &entries)?;
+
+    Ok(path)
+}
+
+// The newest entries matching the query, keyed by entry id, for `crabfeed serve`
+pub async fn query_export_entries(
+    database_url: String,
+    entry_query: &EntryQuery,
+    limit: usize,
+) -> AppResult<Vec<(i64, ExportEntry)>> {
+    let conn = &mut connect(database_url).await?;
+
+    let mut matches = select_query_entries(conn, entry_query).await?;
+    matches.sort_by(|a, b| b.updated.cmp(&a.updated).then(b.id.cmp(&a.id)));
+    let matches = collapse_duplicates(matches);
+
+    let mut entries = vec![];
+    for entry in matches.iter().take(limit) {
+        entries.push((entry.id, load_export_entry(conn, entry).await?));
+    }
+
+    Ok(entries)
+}
+
+pub(crate) async fn load_export_entry(
+    conn: &mut SqliteConnection,
+    entry: &Entry,
+) -> AppResult<ExportEntry> {
+    let feed = select_feed(conn, &entry.feed_id).await?;
+
+    let authors = select_all_entry_authors(conn, &entry.id)
+        .await?
+        .into_iter()
+        .map(|author| author.name)
+        .collect::<Vec<String>>();
+
+    let link = select_all_entry_links(conn, &entry.id)
+        .await?
+        .first()
+        .map(|link| link.href.clone());
+
+    Ok(ExportEntry {
+        title: entry.title.clone().unwrap_or("Untitled Entry".to_string()),
+        feed: feed.display_title().unwrap_or("Untitled Feed".to_string()),
+        author: (!authors.is_empty()).then(|| authors.join(", ")),
+        date: entry.updated,
+        link,
+        content: process_entry_description(conn, entry).await?,
+    })
+}
+
+// Stores a feed read from stdin by `crabfeed import`. Stdin can't be read again, so the
+// feed isn't subscribed to, importing the next document updates it like a fetch would.
+pub async fn import_feed(config: &Settings, document: Vec<u8>) -> AppResult<String> {
+    let feed = parser::parse(decode_feed(document, None).as_slice())?;
+    let title = feed
+        .title
+        .as_ref()
+        .map(|title| title.content.clone())
+        .unwrap_or("Untitled Feed".to_string());
+
+    let conn = &mut connect(config.database_url.clone()).await?;
+    let (_, inserted) = insert_feed(conn, feed, &config.rules).await?;
+
+    // Waited for, the process ends right after
+    run_hooks(prepare_hooks(conn, config, &inserted).await?).await;
+
+    Ok(title)
+}
+
+// Looks a feed up by id or title for `crabfeed export --feed`
+pub async fn feed_entry_ids(database_url: String, feed: &str) -> AppResult<(String, Vec<i64>)> {
+    let conn = &mut connect(database_url).await?;
+
+    let found = select_all_feeds(conn).await?.into_iter().find(|f| {
+        feed.parse::<i64>().is_ok_and(|id| id == f.id)
+            || [&f.custom_title, &f.title]
+                .into_iter()
+                .flatten()
+                .any(|title| title.eq_ignore_ascii_case(feed))
+    });
+
+    let Some(found) = found else {
+        return Err(Error::Generic(format!("No feed found for {feed}")));
+    };
+
+    let entry_ids = select_all_entries(conn, &found.id)
+        .await?
+        .iter()
+        .rev()
+        .map(|entry| entry.id)
+        .collect();
+
+    Ok((
+        found.display_title().unwrap_or("Untitled Feed".to_string()),
+        entry_ids,
+    ))
+}
+
 async fn process_entry_description(
     conn: &mut SqliteConnection,
     entry: &Entry,
 ) -> AppResult<String> {
+    // A fetched full article replaces whatever the feed published
+    if let Some(body) = select_article(conn, &entry.id)
+        .await?
+        .and_then(|article| article.body)
+    {
+        return Ok(body);
+    }
+
     let mut content = String::new();
     let mut summary = String::new();
     let mut description = String::new();
@@ -392,3 +1029,45 @@ async fn process_entry_description(
 
     Ok(entry_description.clone())
 }
+
+// A missing or unreadable state restores the defaults
+async fn load_ui_state(
+    database_url: String,
+    sender: tokio::sync::mpsc::Sender<AppEvent>,
+) -> AppResult<()> {
+    let conn = &mut connect(database_url).await?;
+
+    let state = select_ui_state(conn)
+        .await?
+        .and_then(|state| serde_json::from_str(&state).ok())
+        .unwrap_or_default();
+
+    sender
+        .send(AppEvent::UiState(state))
+        .await
+        .expect("Failed to send AppEvent::UiState");
+
+    sender
+        .send(AppEvent::Complete)
+        .await
+        .expect("Failed to send AppEvent::Complete");
+
+    Ok(())
+}
+
+async fn save_ui_state(
+    database_url: String,
+    state: &UiState,
+    sender: tokio::sync::mpsc::Sender<AppEvent>,
+) -> AppResult<()> {
+    let conn = &mut connect(database_url).await?;
+
+    update_ui_state(conn, &serde_json::to_string(state)?).await?;
+
+    sender
+        .send(AppEvent::Complete)
+        .await
+        .expect("Failed to send AppEvent::Complete");
+
+    Ok(())
+}
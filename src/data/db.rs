@@ -1,8 +1,11 @@
+use std::collections::HashSet;
 use std::str::FromStr;
 
+use crate::config::{Rule, RuleAction};
 use crate::error::Error;
 use crate::prelude::*;
 use crate::AppResult;
+use chrono::NaiveDateTime;
 use feed_rs::model;
 use html_parser::{Dom, Node};
 use log::debug;
@@ -12,6 +15,10 @@ use sqlx::sqlite::SqliteConnectOptions;
 use sqlx::ConnectOptions;
 use sqlx::SqliteConnection;
 
+use super::duplicates::{canonical_link, entry_canonical_link, entry_guid};
+use super::query::{EntryQuery, QueryParam};
+use super::rules::{rule_actions, RuleFeed};
+
 async fn setup_database(conn: &mut SqliteConnection) -> AppResult<()> {
     query!(
         "CREATE TABLE IF NOT EXISTS feed ( \
@@ -189,6 +196,190 @@ async fn setup_database(conn: &mut SqliteConnection) -> AppResult<()> {
     .execute(&mut *conn)
     .await?;
 
+    add_missing_column(conn, "entry", "starred", "BOOLEAN DEFAULT FALSE").await?;
+    add_missing_column(conn, "entry", "hidden", "BOOLEAN DEFAULT FALSE").await?;
+
+    if !has_column(conn, "feed", "url").await? {
+        add_missing_column(conn, "feed", "url", "VARCHAR").await?;
+
+        // Feeds added before there was a url column got their subscription link last
+        query!(
+            r#"
+            UPDATE feed SET url = (
+                SELECT link.href
+                FROM link
+                JOIN feed_link ON link.id = feed_link.link_id
+                WHERE feed_link.feed_id = feed.id
+                ORDER BY link.id DESC
+                LIMIT 1
+            )
+            "#
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    add_missing_column(conn, "feed", "custom_title", "VARCHAR").await?;
+    add_missing_column(conn, "feed", "notes", "TEXT").await?;
+    add_missing_column(conn, "feed", "redirected_from", "VARCHAR").await?;
+    add_missing_column(conn, "feed", "error", "VARCHAR").await?;
+    add_missing_column(conn, "feed", "folder", "VARCHAR").await?;
+
+    add_missing_column(conn, "entry", "guid", "VARCHAR").await?;
+    if !has_column(conn, "entry", "canonical_link").await? {
+        add_missing_column(conn, "entry", "canonical_link", "VARCHAR").await?;
+        backfill_canonical_links(conn).await?;
+    }
+    add_missing_column(conn, "entry", "line_index", "INTEGER DEFAULT 0").await?;
+    query!("CREATE INDEX IF NOT EXISTS entry_guid ON entry (guid)")
+        .execute(&mut *conn)
+        .await?;
+    query!("CREATE INDEX IF NOT EXISTS entry_canonical_link ON entry (canonical_link)")
+        .execute(&mut *conn)
+        .await?;
+
+    query!(
+        "CREATE TABLE IF NOT EXISTS sync_feed ( \
+            feed_id INTEGER PRIMARY KEY NOT NULL, \
+            remote_id VARCHAR NOT NULL UNIQUE, \
+            FOREIGN KEY(feed_id) REFERENCES feed(id) ON DELETE CASCADE \
+        )",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    // Only feeds the sync created are deleted with their subscription
+    add_missing_column(
+        conn,
+        "sync_feed",
+        "created",
+        "BOOLEAN NOT NULL DEFAULT FALSE",
+    )
+    .await?;
+
+    query!(
+        "CREATE TABLE IF NOT EXISTS sync_entry ( \
+            entry_id INTEGER PRIMARY KEY NOT NULL, \
+            remote_id VARCHAR NOT NULL UNIQUE, \
+            FOREIGN KEY(entry_id) REFERENCES entry(id) ON DELETE CASCADE \
+        )",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+        "CREATE TABLE IF NOT EXISTS sync_queue ( \
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL, \
+            entry_id INTEGER NOT NULL, \
+            action VARCHAR NOT NULL, \
+            FOREIGN KEY(entry_id) REFERENCES entry(id) ON DELETE CASCADE \
+        )",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+        "CREATE TABLE IF NOT EXISTS tag ( \
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL, \
+            name VARCHAR NOT NULL UNIQUE \
+        )",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+        "CREATE TABLE IF NOT EXISTS entry_tag ( \
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL, \
+            tag_id INTEGER NOT NULL, \
+            entry_id INTEGER NOT NULL, \
+            FOREIGN KEY(tag_id) REFERENCES tag(id) ON DELETE CASCADE, \
+            FOREIGN KEY(entry_id) REFERENCES entry(id) ON DELETE CASCADE \
+        )",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    // A fetched full article. Its body is a content row like any other, but entry.content_id
+    // already points at what the feed published, and repointing it would orphan that row.
+    // Nor can a content row with a src mark an article, Atom's <content src> sets one too.
+    query!(
+        "CREATE TABLE IF NOT EXISTS entry_article ( \
+            entry_id INTEGER PRIMARY KEY NOT NULL, \
+            content_id INTEGER NOT NULL, \
+            FOREIGN KEY(entry_id) REFERENCES entry(id) ON DELETE CASCADE, \
+            FOREIGN KEY(content_id) REFERENCES content(id) ON DELETE CASCADE \
+        )",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    // A single row holding the UI state as JSON
+    query!(
+        "CREATE TABLE IF NOT EXISTS ui_state ( \
+            id INTEGER PRIMARY KEY NOT NULL CHECK (id = 1), \
+            state TEXT NOT NULL \
+        )",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// Older databases were created before some columns existed, so add them in place
+async fn has_column(conn: &mut SqliteConnection, table: &str, column: &str) -> AppResult<bool> {
+    let found: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pragma_table_info($1) WHERE pragma_table_info.name = $2",
+    )
+    .bind(table)
+    .bind(column)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(found > 0)
+}
+
+// GUIDs were never stored, but links were, so older entries can still be matched by link
+async fn backfill_canonical_links(conn: &mut SqliteConnection) -> AppResult<()> {
+    let links: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT entry_link.entry_id, link.href \
+        FROM entry_link \
+        JOIN link ON link.id = entry_link.link_id \
+        ORDER BY entry_link.id",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut seen = HashSet::new();
+    for (entry_id, href) in links {
+        // Only the first link of each entry
+        if !seen.insert(entry_id) {
+            continue;
+        }
+
+        sqlx::query("UPDATE entry SET canonical_link = $1 WHERE entry.id = $2")
+            .bind(canonical_link(&href))
+            .bind(entry_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+async fn add_missing_column(
+    conn: &mut SqliteConnection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> AppResult<()> {
+    if !has_column(conn, table, column).await? {
+        sqlx::query(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+        ))
+        .execute(&mut *conn)
+        .await?;
+    }
+
     Ok(())
 }
 
@@ -202,7 +393,11 @@ pub async fn connect(database_url: String) -> AppResult<SqliteConnection> {
     Ok(conn)
 }
 
-pub async fn insert_feed(conn: &mut SqliteConnection, feed: model::Feed) -> AppResult<i64> {
+pub async fn insert_feed(
+    conn: &mut SqliteConnection,
+    feed: model::Feed,
+    rules: &[Rule],
+) -> AppResult<(i64, Vec<InsertedEntry>)> {
     debug!("Starting Feed Insertion...");
 
     let mut builder = FeedBuilder::new();
@@ -229,9 +424,9 @@ pub async fn insert_feed(conn: &mut SqliteConnection, feed: model::Feed) -> AppR
     {
         Ok(found_feed) => {
             debug!("Feed is already in DB");
-            insert_entries(conn, feed.entries, found_feed.id).await?;
+            let inserted = insert_entries(conn, feed.entries, found_feed.id, rules).await?;
 
-            return Ok(found_feed.id);
+            Ok((found_feed.id, inserted))
         }
         Err(_) => {
             debug!("Inserting New Feed");
@@ -265,18 +460,20 @@ pub async fn insert_feed(conn: &mut SqliteConnection, feed: model::Feed) -> AppR
             insert_authors(conn, feed.authors, Some(ret_feed.id), None)
                 .await
                 .expect("Failed to insert feed authors");
-            insert_entries(conn, feed.entries, ret_feed.id).await?;
             insert_links(conn, feed.links, Some(ret_feed.id), None)
                 .await
                 .expect("Failed to insert feed links");
+            let inserted = insert_entries(conn, feed.entries, ret_feed.id, rules).await?;
             insert_categories(conn, feed.categories, Some(ret_feed.id), None)
                 .await
                 .expect("Failed to insert feed categories");
-            return Ok(ret_feed.id);
+            Ok((ret_feed.id, inserted))
         }
     }
 }
 
+// Stores the title the feed reports. A custom title set by the user is kept in its
+// own column and still shown in place of this one.
 pub async fn update_feed_title(
     conn: &mut SqliteConnection,
     feed_id: &i64,
@@ -297,6 +494,107 @@ pub async fn update_feed_title(
     Ok(())
 }
 
+pub async fn update_feed_url(
+    conn: &mut SqliteConnection,
+    feed_id: &i64,
+    url: String,
+) -> AppResult<()> {
+    query!(
+        r#"
+        UPDATE feed
+        SET url = $1
+        WHERE feed.id = $2
+        "#,
+        url,
+        feed_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// Remembers why the last update of a feed failed, `None` once it succeeds again
+pub async fn update_feed_error(
+    conn: &mut SqliteConnection,
+    feed_id: &i64,
+    error: Option<String>,
+) -> AppResult<()> {
+    query!(
+        r#"
+        UPDATE feed
+        SET error = $1
+        WHERE feed.id = $2
+        "#,
+        error,
+        feed_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// Follows a permanent redirect, keeping the url the feed was first subscribed with around
+// to show the user and to find its settings by
+pub async fn update_feed_redirect(
+    conn: &mut SqliteConnection,
+    feed_id: &i64,
+    old_url: String,
+    new_url: String,
+) -> AppResult<()> {
+    query!(
+        r#"
+        UPDATE feed
+        SET url = $1, redirected_from = COALESCE(redirected_from, $2)
+        WHERE feed.id = $3
+        "#,
+        new_url,
+        old_url,
+        feed_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// Saves what the user entered in the feed properties popup. An empty title falls
+// back to the one the feed reports, and a new url drops the note of an old move.
+pub async fn update_feed_properties(
+    conn: &mut SqliteConnection,
+    feed_id: &i64,
+    properties: &FeedProperties,
+) -> AppResult<()> {
+    let custom_title = properties
+        .title
+        .clone()
+        .filter(|title| !title.trim().is_empty());
+    let notes = properties
+        .notes
+        .clone()
+        .filter(|notes| !notes.trim().is_empty());
+
+    query!(
+        r#"
+        UPDATE feed
+        SET custom_title = $1,
+            url = $2,
+            notes = $3,
+            redirected_from = CASE WHEN url = $2 THEN redirected_from ELSE NULL END
+        WHERE feed.id = $4
+        "#,
+        custom_title,
+        properties.url,
+        notes,
+        feed_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
 pub async fn select_all_feeds(conn: &mut SqliteConnection) -> AppResult<Vec<Feed>> {
     let results = query_as!(
         Feed,
@@ -513,7 +811,7 @@ pub async fn select_all_entries(
     let entries = query_as!(
         Entry,
         r#"
-        SELECT entry.id, entry.feed_id, entry.title, entry.updated, entry.content_id, entry.media_id, entry.summary, entry.source, entry.read
+        SELECT entry.id, entry.feed_id, entry.title, entry.updated, entry.content_id, entry.media_id, entry.summary, entry.source, entry.read, entry.starred, entry.hidden, entry.guid, entry.canonical_link, entry.line_index
         FROM entry
         JOIN feed ON feed.id = entry.feed_id
         WHERE feed.id = $1
@@ -526,6 +824,50 @@ pub async fn select_all_entries(
     Ok(entries)
 }
 
+pub async fn select_query_entries(
+    conn: &mut SqliteConnection,
+    entry_query: &EntryQuery,
+) -> AppResult<Vec<Entry>> {
+    let sql = format!(
+        "SELECT entry.* FROM entry WHERE {} ORDER BY entry.id",
+        entry_query.filter
+    );
+
+    let mut entries = query_as::<_, Entry>(&sql);
+    for param in entry_query.params.iter() {
+        entries = match param {
+            QueryParam::Text(text) => entries.bind(text.clone()),
+            QueryParam::Date(date) => entries.bind(*date),
+        };
+    }
+
+    Ok(entries.fetch_all(&mut *conn).await?)
+}
+
+// Entries in other feeds with the same GUID or canonical link, see `data::duplicates`.
+// A feed posting the same link twice is a re-post, not a copy.
+pub async fn select_duplicate_ids(
+    conn: &mut SqliteConnection,
+    entry_id: &i64,
+) -> AppResult<Vec<i64>> {
+    let ids = sqlx::query_scalar(
+        r#"
+        SELECT other.id
+        FROM entry
+        JOIN entry AS other
+        ON other.feed_id != entry.feed_id
+        AND (other.guid = entry.guid OR other.canonical_link = entry.canonical_link)
+        WHERE entry.id = $1
+        ORDER BY other.id
+        "#,
+    )
+    .bind(entry_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(ids)
+}
+
 pub async fn mark_entry_read(conn: &mut SqliteConnection, entry_id: &i64) -> AppResult<()> {
     query!(
         r#"
@@ -541,16 +883,133 @@ pub async fn mark_entry_read(conn: &mut SqliteConnection, entry_id: &i64) -> App
     Ok(())
 }
 
-async fn insert_entries(
+// The first line shown when the entry was last left
+pub async fn update_entry_line_index(
+    conn: &mut SqliteConnection,
+    entry_id: &i64,
+    line_index: i64,
+) -> AppResult<()> {
+    query!(
+        r#"
+        UPDATE entry
+        SET line_index = $1
+        WHERE entry.id = $2
+        "#,
+        line_index,
+        entry_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn select_article(
+    conn: &mut SqliteConnection,
+    entry_id: &i64,
+) -> AppResult<Option<Content>> {
+    let result = query_as!(
+        Content,
+        r#"
+        SELECT content.id, content.body, content.content_type, content.length, content.src
+        FROM content
+        JOIN entry_article ON entry_article.content_id = content.id
+        WHERE entry_article.entry_id = $1
+        AND content.body IS NOT NULL
+        "#,
+        entry_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(result)
+}
+
+// Caches a fetched article for the entry, keeping the page it came from as the source
+pub async fn insert_article(
+    conn: &mut SqliteConnection,
+    entry_id: &i64,
+    url: String,
+    body: String,
+) -> AppResult<i64> {
+    let new_link = LinkBuilder::new().href(url).build()?;
+
+    let link_id = query!(
+        r#"
+        INSERT INTO link (href, rel, media_type, href_lang, title, length)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        new_link.href,
+        new_link.rel,
+        new_link.media_type,
+        new_link.href_lang,
+        new_link.title,
+        new_link.length
+    )
+    .execute(&mut *conn)
+    .await?
+    .last_insert_rowid();
+
+    let new_content = ContentBuilder::new()
+        .body(Some(body))
+        .content_type(mime::TEXT_HTML)
+        .length(None)
+        .src(Some(link_id))
+        .build()?;
+
+    let content_id = query!(
+        r#"
+        INSERT INTO content (body, content_type, length, src)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        new_content.body,
+        new_content.content_type,
+        new_content.length,
+        new_content.src
+    )
+    .execute(&mut *conn)
+    .await?
+    .last_insert_rowid();
+
+    query!(
+        r#"
+        INSERT OR REPLACE INTO entry_article (entry_id, content_id)
+        VALUES ($1, $2)
+        "#,
+        entry_id,
+        content_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(content_id)
+}
+
+pub async fn insert_entries(
     conn: &mut SqliteConnection,
     entries: Vec<model::Entry>,
     feed_id: i64,
-) -> AppResult<()> {
+    rules: &[Rule],
+) -> AppResult<Vec<InsertedEntry>> {
     let mut builder = EntryBuilder::new();
+    let mut inserted = vec![];
+
+    let feed = select_feed(conn, &feed_id).await?;
+    let feed_links = select_all_feed_links(conn, &feed_id).await?;
+    let rule_feed = RuleFeed {
+        title: feed.title.as_deref(),
+        urls: feed_links.iter().map(|link| link.href.as_str()).collect(),
+    };
 
     for entry in entries.iter().rev() {
         debug!("Starting Entry Insertion...");
 
+        let actions = rule_actions(rules, &rule_feed, entry);
+        if actions.contains(&RuleAction::Delete) {
+            debug!("Entry dropped by rule");
+            continue;
+        }
+
         let content_id = insert_content(conn, entry.content.clone())
             .await
             .expect("Failed to insert entry content");
@@ -567,15 +1026,20 @@ async fn insert_entries(
             .media_id(media_id)
             .summary(entry.summary.clone())
             .source(entry.source.clone())
+            .guid(entry_guid(entry))
+            .canonical_link(entry_canonical_link(entry))
             .build()?;
 
+        // Only the same feed counts, copies in other feeds are found as duplicates instead
         let possible_entries = query_as!(
             Entry,
             r#"
             SELECT *
             FROM entry
-            WHERE entry.title = ?
+            WHERE entry.feed_id = $1
+            AND entry.title = $2
             "#,
+            new_entry.feed_id,
             new_entry.title
         )
         .fetch_all(&mut *conn)
@@ -585,7 +1049,17 @@ async fn insert_entries(
             debug!("Inserting new entry...");
             query!(
                 r#"
-                INSERT INTO entry (feed_id, title, updated, content_id, summary, source, media_id)
+                INSERT INTO entry (
+                    feed_id,
+                    title,
+                    updated,
+                    content_id,
+                    summary,
+                    source,
+                    media_id,
+                    guid,
+                    canonical_link
+                )
                 VALUES (
                     $1,
                     $2,
@@ -593,7 +1067,9 @@ async fn insert_entries(
                     $4,
                     $5,
                     $6,
-                    $7
+                    $7,
+                    $8,
+                    $9
                 )
                 "#,
                 new_entry.feed_id,
@@ -602,7 +1078,9 @@ async fn insert_entries(
                 new_entry.content_id,
                 new_entry.summary,
                 new_entry.source,
-                new_entry.media_id
+                new_entry.media_id,
+                new_entry.guid,
+                new_entry.canonical_link
             )
             .execute(&mut *conn)
             .await
@@ -623,22 +1101,146 @@ async fn insert_entries(
             .fetch_one(&mut *conn)
             .await?;
 
-            debug!("Populating Entry data...");
-            insert_authors(conn, entry.authors.clone(), None, Some(ret_entry.id))
-                .await
-                .expect("Failed to insert entry authors");
-            insert_links(conn, entry.links.clone(), None, Some(ret_entry.id))
-                .await
-                .expect("Failed to insert entry links");
-            insert_categories(conn, entry.categories.clone(), None, Some(ret_entry.id))
-                .await
-                .expect("Failed to insert entry categories");
-        }
-    }
+            debug!("Populating Entry data...");
+            insert_authors(conn, entry.authors.clone(), None, Some(ret_entry.id))
+                .await
+                .expect("Failed to insert entry authors");
+            insert_links(conn, entry.links.clone(), None, Some(ret_entry.id))
+                .await
+                .expect("Failed to insert entry links");
+            insert_categories(conn, entry.categories.clone(), None, Some(ret_entry.id))
+                .await
+                .expect("Failed to insert entry categories");
+
+            apply_rule_actions(conn, &ret_entry.id, &actions).await?;
+
+            inserted.push(InsertedEntry {
+                id: ret_entry.id,
+                feed_id,
+                hooks: actions
+                    .into_iter()
+                    .filter_map(|action| match action {
+                        RuleAction::Hook(command) => Some(command),
+                        _ => None,
+                    })
+                    .collect(),
+            });
+        }
+    }
+
+    Ok(inserted)
+}
+
+async fn apply_rule_actions(
+    conn: &mut SqliteConnection,
+    entry_id: &i64,
+    actions: &[RuleAction],
+) -> AppResult<()> {
+    for action in actions {
+        debug!("Applying rule action {:?}", action);
+        match action {
+            RuleAction::MarkRead => mark_entry_read(conn, entry_id).await?,
+            RuleAction::Star => mark_entry_starred(conn, entry_id, true).await?,
+            RuleAction::Tag(name) => insert_entry_tag(conn, entry_id, name.clone()).await?,
+            RuleAction::Hide => mark_entry_hidden(conn, entry_id, true).await?,
+            // Deleted entries never get here and hooks run after the update, see `data::hooks`
+            RuleAction::Delete | RuleAction::Hook(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn mark_entry_starred(
+    conn: &mut SqliteConnection,
+    entry_id: &i64,
+    starred: bool,
+) -> AppResult<()> {
+    query!(
+        r#"
+        UPDATE entry
+        SET starred = $1
+        WHERE entry.id = $2
+        "#,
+        starred,
+        entry_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn mark_entry_hidden(
+    conn: &mut SqliteConnection,
+    entry_id: &i64,
+    hidden: bool,
+) -> AppResult<()> {
+    query!(
+        r#"
+        UPDATE entry
+        SET hidden = $1
+        WHERE entry.id = $2
+        "#,
+        hidden,
+        entry_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn insert_entry_tag(
+    conn: &mut SqliteConnection,
+    entry_id: &i64,
+    name: String,
+) -> AppResult<()> {
+    query!(
+        r#"
+        INSERT OR IGNORE INTO tag (name)
+        VALUES ($1)
+        "#,
+        name
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+        r#"
+        INSERT INTO entry_tag (tag_id, entry_id)
+        SELECT tag.id, $1
+        FROM tag
+        WHERE tag.name = $2
+        "#,
+        entry_id,
+        name
+    )
+    .execute(&mut *conn)
+    .await?;
 
     Ok(())
 }
 
+pub async fn select_all_entry_tags(
+    conn: &mut SqliteConnection,
+    entry_id: &i64,
+) -> AppResult<Vec<String>> {
+    let result = query!(
+        r#"
+        SELECT tag.name
+        FROM tag
+        JOIN entry_tag ON tag.id = entry_tag.tag_id
+        WHERE entry_tag.entry_id = $1
+        "#,
+        entry_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(result.into_iter().map(|row| row.name).collect())
+}
+
 async fn insert_authors(
     conn: &mut SqliteConnection,
     authors: Vec<model::Person>,
@@ -1245,6 +1847,362 @@ pub async fn select_media(conn: &mut SqliteConnection, media_id: &i64) -> AppRes
     Ok(result)
 }
 
+pub async fn update_entry_read(
+    conn: &mut SqliteConnection,
+    entry_id: &i64,
+    read: bool,
+) -> AppResult<()> {
+    query!(
+        r#"
+        UPDATE entry
+        SET read = $1
+        WHERE entry.id = $2
+        "#,
+        read,
+        entry_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// Entries are told apart by title when they are inserted, so an entry that was
+// skipped as already known is found again by its title within the same feed
+pub async fn select_entry_by_title(
+    conn: &mut SqliteConnection,
+    feed_id: &i64,
+    title: &str,
+) -> AppResult<Option<Entry>> {
+    let entry = query_as!(
+        Entry,
+        r#"
+        SELECT entry.id, entry.feed_id, entry.title, entry.updated, entry.content_id, entry.media_id, entry.summary, entry.source, entry.read, entry.starred, entry.hidden, entry.guid, entry.canonical_link, entry.line_index
+        FROM entry
+        WHERE entry.title = $1
+        AND entry.feed_id = $2
+        ORDER BY entry.id
+        LIMIT 1
+        "#,
+        title,
+        feed_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(entry)
+}
+
+pub async fn select_feed_by_url(conn: &mut SqliteConnection, url: &str) -> AppResult<Option<Feed>> {
+    let feed = query_as!(
+        Feed,
+        r#"
+        SELECT *
+        FROM feed
+        WHERE feed.url = $1
+        "#,
+        url
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(feed)
+}
+
+// Creates the row for a feed known from the sync server before any of its entries
+pub async fn insert_remote_feed(
+    conn: &mut SqliteConnection,
+    title: &str,
+    url: &str,
+) -> AppResult<i64> {
+    let result = query!(
+        r#"
+        INSERT INTO feed (title, url)
+        VALUES ($1, $2)
+        "#,
+        title,
+        url
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+pub async fn update_feed_folder(
+    conn: &mut SqliteConnection,
+    feed_id: &i64,
+    folder: Option<String>,
+) -> AppResult<()> {
+    query!(
+        r#"
+        UPDATE feed
+        SET folder = $1
+        WHERE feed.id = $2
+        "#,
+        folder,
+        feed_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn select_sync_feeds(conn: &mut SqliteConnection) -> AppResult<Vec<SyncFeed>> {
+    let feeds = query_as!(
+        SyncFeed,
+        r#"
+        SELECT sync_feed.feed_id AS local_id, sync_feed.remote_id, sync_feed.created
+        FROM sync_feed
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(feeds)
+}
+
+pub async fn insert_sync_feed(
+    conn: &mut SqliteConnection,
+    feed_id: &i64,
+    remote_id: &str,
+    created: bool,
+) -> AppResult<()> {
+    query!(
+        r#"
+        INSERT OR REPLACE INTO sync_feed (feed_id, remote_id, created)
+        VALUES ($1, $2, $3)
+        "#,
+        feed_id,
+        remote_id,
+        created
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// Unlinks a feed that was subscribed before the sync found it, keeping it and its entries
+pub async fn delete_sync_feed(conn: &mut SqliteConnection, feed_id: &i64) -> AppResult<()> {
+    query!(
+        r#"
+        DELETE FROM sync_entry
+        WHERE sync_entry.entry_id IN (
+            SELECT entry.id
+            FROM entry
+            WHERE entry.feed_id = $1
+        )
+        "#,
+        feed_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    query!(
+        r#"
+        DELETE FROM sync_feed
+        WHERE sync_feed.feed_id = $1
+        "#,
+        feed_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn select_sync_entries(conn: &mut SqliteConnection) -> AppResult<Vec<RemoteId>> {
+    let entries = query_as!(
+        RemoteId,
+        r#"
+        SELECT sync_entry.entry_id AS local_id, sync_entry.remote_id
+        FROM sync_entry
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(entries)
+}
+
+// Whether the entry was linked, an entry already known by another remote id keeps it
+pub async fn insert_sync_entry(
+    conn: &mut SqliteConnection,
+    entry_id: &i64,
+    remote_id: &str,
+) -> AppResult<bool> {
+    let result = query!(
+        r#"
+        INSERT OR IGNORE INTO sync_entry (entry_id, remote_id)
+        VALUES ($1, $2)
+        "#,
+        entry_id,
+        remote_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Only entries the server knows about are queued. A newer change to the same
+// state replaces one still waiting, so marking read and unread again sends nothing twice.
+pub async fn insert_sync_change(
+    conn: &mut SqliteConnection,
+    entry_id: &i64,
+    action: &str,
+    replaces: &[&str],
+) -> AppResult<()> {
+    for replaced in replaces {
+        query!(
+            r#"
+            DELETE FROM sync_queue
+            WHERE sync_queue.entry_id = $1
+            AND sync_queue.action = $2
+            "#,
+            entry_id,
+            replaced
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    query!(
+        r#"
+        INSERT INTO sync_queue (entry_id, action)
+        SELECT sync_entry.entry_id, $2
+        FROM sync_entry
+        WHERE sync_entry.entry_id = $1
+        "#,
+        entry_id,
+        action
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn select_sync_queue(conn: &mut SqliteConnection) -> AppResult<Vec<QueuedChange>> {
+    let changes = query_as!(
+        QueuedChange,
+        r#"
+        SELECT sync_queue.id, sync_queue.action, sync_entry.remote_id
+        FROM sync_queue
+        JOIN sync_entry ON sync_entry.entry_id = sync_queue.entry_id
+        ORDER BY sync_queue.id
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(changes)
+}
+
+pub async fn delete_sync_change(conn: &mut SqliteConnection, change_id: &i64) -> AppResult<()> {
+    query!(
+        r#"
+        DELETE FROM sync_queue
+        WHERE sync_queue.id = $1
+        "#,
+        change_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// Per feed: the number of entries, how many of them are read and the newest entry date
+pub async fn select_feed_entry_counts(
+    conn: &mut SqliteConnection,
+) -> AppResult<Vec<(i64, i64, i64, Option<NaiveDateTime>)>> {
+    let counts = sqlx::query_as(
+        r#"
+        SELECT
+            feed.id,
+            COUNT(entry.id),
+            COALESCE(SUM(CASE WHEN entry.read THEN 1 ELSE 0 END), 0),
+            MAX(entry.updated)
+        FROM feed
+        LEFT JOIN entry ON entry.feed_id = feed.id
+        GROUP BY feed.id
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(counts)
+}
+
+// Entries per feed and week for the `weeks` weeks up to `now`, week 0 being the last 7 days
+pub async fn select_weekly_entry_counts(
+    conn: &mut SqliteConnection,
+    now: NaiveDateTime,
+    weeks: i64,
+) -> AppResult<Vec<(i64, i64, i64)>> {
+    let counts = sqlx::query_as(
+        r#"
+        SELECT
+            entry.feed_id,
+            CAST((julianday($1) - julianday(entry.updated)) / 7 AS INTEGER) AS week,
+            COUNT(*)
+        FROM entry
+        WHERE entry.updated IS NOT NULL
+        AND julianday(entry.updated) <= julianday($1)
+        AND julianday($1) - julianday(entry.updated) < $2 * 7
+        GROUP BY entry.feed_id, week
+        "#,
+    )
+    .bind(now)
+    .bind(weeks)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(counts)
+}
+
+pub async fn select_database_size(conn: &mut SqliteConnection) -> AppResult<i64> {
+    let size = sqlx::query_scalar(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(size)
+}
+
+pub async fn select_ui_state(conn: &mut SqliteConnection) -> AppResult<Option<String>> {
+    let state = query!(
+        r#"
+        SELECT ui_state.state
+        FROM ui_state
+        WHERE ui_state.id = 1
+        "#
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(state.map(|row| row.state))
+}
+
+pub async fn update_ui_state(conn: &mut SqliteConnection, state: &str) -> AppResult<()> {
+    query!(
+        r#"
+        INSERT INTO ui_state (id, state)
+        VALUES (1, $1)
+        ON CONFLICT(id) DO UPDATE SET state = excluded.state
+        "#,
+        state
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
 pub async fn delete_feed(conn: &mut SqliteConnection, feed_id: i64) -> AppResult<()> {
     // Get all the entries for the feed
     // delete each entry's link, content and author
@@ -1348,6 +2306,27 @@ pub async fn delete_entry(conn: &mut SqliteConnection, entry_id: i64) -> AppResu
         }
     }
 
+    if let Some(article) = select_article(conn, &entry.id).await? {
+        query!(
+            r#"
+            DELETE FROM link
+            WHERE link.id = $1
+            "#,
+            article.src
+        )
+        .execute(&mut *conn)
+        .await?;
+        query!(
+            r#"
+            DELETE FROM content
+            WHERE content.id = $1
+            "#,
+            article.id
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
     if let Some(media_id) = entry.media_id {
         if let Ok(media_links) = select_all_media_links(conn, &media_id).await {
             for link in media_links {
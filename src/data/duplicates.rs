@@ -0,0 +1,97 @@
+// Aggregators and mirrors republish the same article under another feed. Copies are
+// recognised by their GUID or by their canonical link: the entry's link without
+// scheme, `www.`, fragment, tracking parameters and trailing slash. Only GUIDs that
+// look globally unique count, plain numbers and short words are feed specific.
+
+use std::collections::HashSet;
+
+use feed_rs::model;
+use reqwest::Url;
+
+use crate::prelude::Entry;
+
+// Shorter GUIDs without a scheme, like "1234", are only unique within their feed
+const MIN_GUID_LENGTH: usize = 32;
+
+pub fn entry_guid(entry: &model::Entry) -> Option<String> {
+    let id = entry.id.trim();
+
+    (id.contains(':') || id.len() >= MIN_GUID_LENGTH).then(|| id.to_string())
+}
+
+// The link marked canonical, else the main one
+pub fn entry_canonical_link(entry: &model::Entry) -> Option<String> {
+    let link = entry
+        .links
+        .iter()
+        .find(|link| link.rel.as_deref() == Some("canonical"))
+        .or_else(|| {
+            entry
+                .links
+                .iter()
+                .find(|link| matches!(link.rel.as_deref(), None | Some("alternate")))
+        })
+        .or(entry.links.first())?;
+
+    canonical_link(&link.href)
+}
+
+// `https://www.example.com/post/?utm_source=feed#top` becomes `example.com/post`
+pub fn canonical_link(href: &str) -> Option<String> {
+    let href = href.trim();
+    if href.is_empty() {
+        return None;
+    }
+
+    let Ok(url) = Url::parse(href) else {
+        return Some(href.trim_end_matches('/').to_string());
+    };
+
+    let host = url.host_str().unwrap_or_default();
+    let host = host.strip_prefix("www.").unwrap_or(host);
+
+    let authority = match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    };
+
+    let mut link = format!("{authority}{}", url.path().trim_end_matches('/'));
+
+    let query: Vec<String> = url
+        .query_pairs()
+        .filter(|(key, _)| !key.starts_with("utm_"))
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect();
+    if !query.is_empty() {
+        link.push('?');
+        link.push_str(&query.join("&"));
+    }
+
+    Some(link)
+}
+
+// Keeps the first copy of every entry, for views that gather entries from many feeds
+pub fn collapse_duplicates(entries: Vec<Entry>) -> Vec<Entry> {
+    let mut guids = HashSet::new();
+    let mut links = HashSet::new();
+
+    entries
+        .into_iter()
+        .filter(|entry| {
+            let seen = entry.guid.as_ref().is_some_and(|guid| guids.contains(guid))
+                || entry
+                    .canonical_link
+                    .as_ref()
+                    .is_some_and(|link| links.contains(link));
+
+            if let Some(guid) = &entry.guid {
+                guids.insert(guid.clone());
+            }
+            if let Some(link) = &entry.canonical_link {
+                links.insert(link.clone());
+            }
+
+            !seen
+        })
+        .collect()
+}
@@ -0,0 +1,57 @@
+// Turns downloaded documents into UTF-8. A byte order mark wins, then the
+// encoding declared in the XML prolog, then the charset the server sent.
+// Anything else is taken to be UTF-8.
+
+use encoding_rs::{Encoding, UTF_8};
+use regex::bytes::Regex;
+
+// Only the start of the document is searched for the prolog
+const PROLOG_LENGTH: usize = 1024;
+
+pub fn detect_encoding(body: &[u8], charset: Option<&str>) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(body) {
+        return encoding;
+    }
+
+    prolog_encoding(body)
+        .or_else(|| charset.and_then(|charset| Encoding::for_label(charset.as_bytes())))
+        .unwrap_or(UTF_8)
+}
+
+// The document as UTF-8, with the prolog rewritten to say so for the parser
+pub fn decode_feed(body: Vec<u8>, charset: Option<&str>) -> Vec<u8> {
+    let encoding = detect_encoding(&body, charset);
+    let (text, _) = encoding.decode_with_bom_removal(&body);
+
+    let text = text.into_owned().into_bytes();
+    match declaration()
+        .captures(&text)
+        .and_then(|captures| captures.get(1))
+    {
+        Some(label) => [&text[..label.start()], b"UTF-8", &text[label.end()..]].concat(),
+        None => text,
+    }
+}
+
+pub fn decode_text(body: &[u8], charset: Option<&str>) -> String {
+    let encoding = charset
+        .and_then(|charset| Encoding::for_label(charset.as_bytes()))
+        .unwrap_or(UTF_8);
+
+    encoding.decode(body).0.into_owned()
+}
+
+fn prolog_encoding(body: &[u8]) -> Option<&'static Encoding> {
+    let start = &body[..body.len().min(PROLOG_LENGTH)];
+    let label = declaration().captures(start)?.get(1)?;
+
+    // A document that could be read far enough to find its declaration is not
+    // UTF-16, whatever it claims
+    Encoding::for_label(label.as_bytes()).map(|encoding| encoding.output_encoding())
+}
+
+// Captures the label in `<?xml version="1.0" encoding="..."?>`
+fn declaration() -> Regex {
+    Regex::new(r#"^\s*<\?xml[^>]*?encoding\s*=\s*["']([^"']*)["']"#)
+        .expect("Invalid encoding declaration pattern")
+}
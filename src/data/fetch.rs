@@ -0,0 +1,319 @@
+// Downloads feeds and articles through clients built from the `http` settings.
+// Redirects are followed by hand so permanent moves can be recorded.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+
+use crate::config::{FeedAuth, HttpSettings, Secret, Settings};
+use crate::error::Error;
+use crate::AppResult;
+use log::debug;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Proxy, RequestBuilder, Response, StatusCode, Url};
+
+pub const MAX_REDIRECTS: usize = 10;
+
+pub const DEFAULT_USER_AGENT: &str = concat!("crabfeed/", env!("CARGO_PKG_VERSION"));
+const DEFAULT_CONNECT_TIMEOUT: u64 = 10;
+const DEFAULT_READ_TIMEOUT: u64 = 30;
+const DEFAULT_TOTAL_TIMEOUT: u64 = 60;
+pub const DEFAULT_MAX_RESPONSE_SIZE: u64 = 16 * 1024 * 1024;
+
+// How much of a body `read_body` takes and how long it waits for each part of it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodyLimits {
+    pub max_size: u64,
+    pub read_timeout: Duration,
+}
+
+impl BodyLimits {
+    pub fn new(settings: &HttpSettings) -> Self {
+        Self {
+            max_size: settings
+                .max_response_size
+                .unwrap_or(DEFAULT_MAX_RESPONSE_SIZE),
+            read_timeout: Duration::from_secs(
+                settings.read_timeout.unwrap_or(DEFAULT_READ_TIMEOUT),
+            ),
+        }
+    }
+}
+
+struct ConfiguredClient {
+    client: Client,
+    limits: BodyLimits,
+}
+
+impl ConfiguredClient {
+    fn new(settings: &HttpSettings) -> AppResult<Self> {
+        Ok(Self {
+            client: build_client(settings)?,
+            limits: BodyLimits::new(settings),
+        })
+    }
+}
+
+// One client for the global settings and one for each feed that overrides them
+pub struct HttpClients {
+    default: ConfiguredClient,
+    feeds: HashMap<String, ConfiguredClient>,
+}
+
+impl HttpClients {
+    pub fn new(settings: &Settings) -> AppResult<Self> {
+        let default = ConfiguredClient::new(&settings.http)?;
+        let mut feeds = HashMap::new();
+
+        for feed in settings.feeds.iter() {
+            if let Some(overrides) = &feed.http {
+                feeds.insert(
+                    feed.url.clone(),
+                    ConfiguredClient::new(&settings.http.merge(overrides))?,
+                );
+            }
+        }
+
+        Ok(Self { default, feeds })
+    }
+
+    fn configured(&self, url: &str) -> &ConfiguredClient {
+        self.feeds.get(url).unwrap_or(&self.default)
+    }
+
+    // The client for a feed's subscription url
+    pub fn client(&self, url: &str) -> &Client {
+        &self.configured(url).client
+    }
+
+    pub fn limits(&self, url: &str) -> BodyLimits {
+        self.configured(url).limits
+    }
+
+    pub fn default_client(&self) -> &Client {
+        &self.default.client
+    }
+
+    pub fn default_limits(&self) -> BodyLimits {
+        self.default.limits
+    }
+}
+
+pub fn build_client(settings: &HttpSettings) -> AppResult<Client> {
+    build_with_redirects(settings, Policy::none())
+}
+
+// The sync server is talked to directly rather than through `fetch`, so its client
+// follows redirects itself, e.g. from http to https
+pub fn build_sync_client(settings: &HttpSettings) -> AppResult<Client> {
+    build_with_redirects(settings, Policy::limited(MAX_REDIRECTS))
+}
+
+fn build_with_redirects(settings: &HttpSettings, policy: Policy) -> AppResult<Client> {
+    let user_agent = settings
+        .user_agent
+        .clone()
+        .unwrap_or(DEFAULT_USER_AGENT.to_string());
+
+    let mut builder = Client::builder()
+        .redirect(policy)
+        .user_agent(user_agent)
+        .connect_timeout(Duration::from_secs(
+            settings.connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+        ))
+        .timeout(Duration::from_secs(
+            settings.total_timeout.unwrap_or(DEFAULT_TOTAL_TIMEOUT),
+        ));
+
+    if let Some(proxy) = &settings.proxy {
+        builder = builder.proxy(Proxy::all(proxy)?);
+    }
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in settings.headers.iter() {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| Error::Generic(format!("Invalid header name {name}")))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| Error::Generic(format!("Invalid value for header {name}")))?;
+        headers.insert(name, value);
+    }
+
+    Ok(builder.default_headers(headers).build()?)
+}
+
+pub struct Fetched {
+    pub response: Response,
+    // Where the feed now lives if every redirect on the way was permanent
+    pub moved_to: Option<String>,
+}
+
+pub async fn fetch(client: &Client, url: &str) -> AppResult<Fetched> {
+    fetch_authenticated(client, url, None).await
+}
+
+// Credentials are only sent to the host the feed was subscribed at, never to
+// wherever it redirects
+pub async fn fetch_authenticated(
+    client: &Client,
+    url: &str,
+    credentials: Option<&Credentials>,
+) -> AppResult<Fetched> {
+    let mut current =
+        Url::parse(url).map_err(|e| Error::Generic(format!("Invalid url {url}: {e}")))?;
+    let origin = current.clone();
+    let mut moved_to = None;
+    // A temporary redirect anywhere in the chain means the original url stays
+    let mut permanent = true;
+
+    for _ in 0..=MAX_REDIRECTS {
+        let mut request = client.get(current.clone());
+        if let Some(credentials) = credentials.filter(|_| same_origin(&origin, &current)) {
+            request = credentials.apply(request);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+            return Err(Error::Unauthorized(status));
+        }
+
+        if !status.is_redirection() && !status.is_success() {
+            return Err(Error::HttpStatus(status));
+        }
+
+        if !status.is_redirection() {
+            return Ok(Fetched { response, moved_to });
+        }
+
+        let Some(location) = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+        else {
+            return Ok(Fetched { response, moved_to });
+        };
+
+        let next = current
+            .join(location)
+            .map_err(|e| Error::Generic(format!("Invalid redirect to {location}: {e}")))?;
+
+        permanent &= matches!(
+            status,
+            StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT
+        );
+        if permanent {
+            moved_to = Some(next.to_string());
+        }
+
+        debug!("{current} redirects to {next} ({status})");
+        current = next;
+    }
+
+    Err(Error::Generic(format!(
+        "Too many redirects for {url}, gave up after {MAX_REDIRECTS}"
+    )))
+}
+
+// Reads the body in chunks so an oversized one is dropped before it is all in memory,
+// and a server that stops sending is given up on. Compressed bodies are counted after
+// decompression.
+pub async fn read_body(mut response: Response, limits: BodyLimits) -> AppResult<Vec<u8>> {
+    if response
+        .content_length()
+        .is_some_and(|length| length > limits.max_size)
+    {
+        return Err(Error::ResponseTooLarge(limits.max_size));
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = tokio::time::timeout(limits.read_timeout, response.chunk())
+        .await
+        .map_err(|_| Error::ReadTimeout(limits.read_timeout.as_secs()))??
+    {
+        if (body.len() + chunk.len()) as u64 > limits.max_size {
+            return Err(Error::ResponseTooLarge(limits.max_size));
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(body)
+}
+
+// The charset parameter of a Content-Type header
+pub fn content_charset(response: &Response) -> Option<String> {
+    let content_type = response.headers().get(CONTENT_TYPE)?.to_str().ok()?;
+
+    content_type.split(';').skip(1).find_map(|parameter| {
+        let (name, value) = parameter.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+fn same_origin(a: &Url, b: &Url) -> bool {
+    a.scheme() == b.scheme()
+        && a.host_str() == b.host_str()
+        && a.port_or_known_default() == b.port_or_known_default()
+}
+
+pub enum Credentials {
+    Basic { username: String, password: String },
+    Bearer(String),
+}
+
+impl Credentials {
+    // Secrets from the config are looked up each time so rotated ones are picked up
+    pub async fn resolve(auth: &FeedAuth) -> AppResult<Self> {
+        match auth {
+            FeedAuth::Basic { username, password } => Ok(Credentials::Basic {
+                username: username.clone(),
+                password: resolve_secret(password).await?,
+            }),
+            FeedAuth::Bearer { token } => Ok(Credentials::Bearer(resolve_secret(token).await?)),
+        }
+    }
+
+    // reqwest marks both headers as sensitive, so they are left out of its debug output
+    fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        match self {
+            Credentials::Basic { username, password } => {
+                request.basic_auth(username, Some(password))
+            }
+            Credentials::Bearer(token) => request.bearer_auth(token),
+        }
+    }
+}
+
+pub async fn resolve_secret(secret: &Secret) -> AppResult<String> {
+    match secret {
+        Secret::Plain(value) => Ok(value.clone()),
+        Secret::Env { env } => std::env::var(env)
+            .map_err(|_| Error::Secret(format!("environment variable {env} is not set"))),
+        Secret::Command { command } => {
+            let output = tokio::process::Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .stdin(Stdio::null())
+                .output()
+                .await?;
+
+            // The command's output is the secret, so it is never part of the error
+            if !output.status.success() {
+                return Err(Error::Secret(format!(
+                    "`{command}` failed ({})",
+                    output.status
+                )));
+            }
+
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .map(|line| line.to_string())
+                .filter(|line| !line.is_empty())
+                .ok_or_else(|| Error::Secret(format!("`{command}` printed nothing")))
+        }
+    }
+}
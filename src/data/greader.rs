@@ -0,0 +1,336 @@
+// Client for the Google Reader API spoken by FreshRSS, Inoreader and others.
+// `url` is the API root, for FreshRSS https://host/api/greader.php.
+//
+// Items come in two id forms, `tag:google.com,2005:reader/item/<hex>` in stream
+// contents and a decimal number in id lists. The decimal one is stored.
+
+use std::collections::HashSet;
+
+use chrono::DateTime;
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::AppResult;
+
+use super::sync::{check_status, RemoteEntry, RemoteFeed, RemoteState, SyncAction};
+
+const READING_LIST: &str = "user/-/state/com.google/reading-list";
+const READ: &str = "user/-/state/com.google/read";
+const STARRED: &str = "user/-/state/com.google/starred";
+const LABEL_PREFIX: &str = "user/-/label/";
+const ITEM_PREFIX: &str = "tag:google.com,2005:reader/item/";
+
+const PAGE_SIZE: usize = 100;
+// At most this many entries are pulled per sync, however far behind the database is
+pub const MAX_ENTRIES: usize = 1000;
+const IDS_PAGE_SIZE: usize = 10000;
+
+pub struct GoogleReader {
+    client: Client,
+    url: String,
+    auth: String,
+    // Needed for writes, fetched with the first one
+    token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SubscriptionList {
+    subscriptions: Vec<Subscription>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Subscription {
+    id: String,
+    title: String,
+    url: Option<String>,
+    html_url: Option<String>,
+    #[serde(default)]
+    categories: Vec<Category>,
+}
+
+#[derive(Deserialize)]
+struct Category {
+    id: String,
+    label: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StreamContents {
+    #[serde(default)]
+    items: Vec<Item>,
+    continuation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Item {
+    id: String,
+    title: Option<String>,
+    published: Option<i64>,
+    #[serde(default)]
+    canonical: Vec<Href>,
+    #[serde(default)]
+    alternate: Vec<Href>,
+    summary: Option<ItemContent>,
+    content: Option<ItemContent>,
+    author: Option<String>,
+    origin: Origin,
+}
+
+#[derive(Deserialize)]
+struct Href {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct ItemContent {
+    content: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Origin {
+    stream_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ItemIds {
+    #[serde(default)]
+    item_refs: Vec<ItemRef>,
+    continuation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ItemRef {
+    id: String,
+}
+
+impl GoogleReader {
+    pub async fn login(
+        client: Client,
+        url: &str,
+        username: &str,
+        password: &str,
+    ) -> AppResult<Self> {
+        let url = url.trim_end_matches('/').to_string();
+
+        let response = client
+            .post(format!("{url}/accounts/ClientLogin"))
+            .form(&[("Email", username), ("Passwd", password)])
+            .send()
+            .await?;
+        let body = check_status(response)?.text().await?;
+
+        let auth = body
+            .lines()
+            .find_map(|line| line.strip_prefix("Auth="))
+            .ok_or(Error::Static("The sync server sent no auth token"))?
+            .trim()
+            .to_string();
+
+        Ok(Self {
+            client,
+            url,
+            auth,
+            token: None,
+        })
+    }
+
+    pub async fn subscriptions(&self) -> AppResult<Vec<RemoteFeed>> {
+        let list: SubscriptionList = self
+            .get("subscription/list", &[("output", "json".to_string())])
+            .await?;
+
+        Ok(list
+            .subscriptions
+            .into_iter()
+            .map(|subscription| {
+                let folder = subscription.categories.into_iter().find_map(|category| {
+                    category.label.or_else(|| {
+                        category
+                            .id
+                            .strip_prefix(LABEL_PREFIX)
+                            .map(|label| label.to_string())
+                    })
+                });
+
+                RemoteFeed {
+                    url: subscription
+                        .url
+                        .or(subscription.html_url)
+                        .unwrap_or_else(|| subscription.id.trim_start_matches("feed/").to_string()),
+                    id: subscription.id,
+                    title: subscription.title,
+                    folder,
+                }
+            })
+            .collect())
+    }
+
+    // Pages through the reading list until a page holds nothing new
+    pub async fn entries(&self, known: &HashSet<String>) -> AppResult<Vec<RemoteEntry>> {
+        let mut entries = vec![];
+        let mut continuation = None;
+
+        while entries.len() < MAX_ENTRIES {
+            let mut params = vec![("output", "json".to_string()), ("n", PAGE_SIZE.to_string())];
+            if let Some(continuation) = continuation.take() {
+                params.push(("c", continuation));
+            }
+
+            let page: StreamContents = self
+                .get(&format!("stream/contents/{READING_LIST}"), &params)
+                .await?;
+
+            let page_entries: Vec<RemoteEntry> = page.items.into_iter().map(remote_entry).collect();
+            let all_known = page_entries.iter().all(|entry| known.contains(&entry.id));
+            entries.extend(page_entries);
+
+            match page.continuation {
+                Some(next) if !all_known && !next.is_empty() => continuation = Some(next),
+                _ => break,
+            }
+        }
+
+        entries.truncate(MAX_ENTRIES);
+        Ok(entries)
+    }
+
+    pub async fn state(&self) -> AppResult<RemoteState> {
+        Ok(RemoteState {
+            unread: self.item_ids(READING_LIST, Some(READ)).await?,
+            starred: self.item_ids(STARRED, None).await?,
+        })
+    }
+
+    pub async fn push(&mut self, action: SyncAction, remote_ids: &[String]) -> AppResult<()> {
+        let token = match &self.token {
+            Some(token) => token.clone(),
+            None => {
+                let response = self
+                    .client
+                    .get(format!("{}/reader/api/0/token", self.url))
+                    .header("Authorization", self.authorization())
+                    .send()
+                    .await?;
+                let token = check_status(response)?.text().await?.trim().to_string();
+                self.token = Some(token.clone());
+                token
+            }
+        };
+
+        let (key, tag) = match action {
+            SyncAction::Read => ("a", READ),
+            SyncAction::Unread => ("r", READ),
+            SyncAction::Star => ("a", STARRED),
+            SyncAction::Unstar => ("r", STARRED),
+        };
+
+        let mut form = vec![("T", token), (key, tag.to_string())];
+        form.extend(remote_ids.iter().map(|id| ("i", long_id(id))));
+
+        let response = self
+            .client
+            .post(format!("{}/reader/api/0/edit-tag", self.url))
+            .header("Authorization", self.authorization())
+            .form(&form)
+            .send()
+            .await?;
+        check_status(response)?;
+
+        Ok(())
+    }
+
+    // Every id in the stream, entries missing from a partial list would be taken as read
+    async fn item_ids(&self, stream: &str, exclude: Option<&str>) -> AppResult<HashSet<String>> {
+        let mut ids = HashSet::new();
+        let mut continuation = None;
+
+        loop {
+            let mut params = vec![
+                ("output", "json".to_string()),
+                ("s", stream.to_string()),
+                ("n", IDS_PAGE_SIZE.to_string()),
+            ];
+            if let Some(exclude) = exclude {
+                params.push(("xt", exclude.to_string()));
+            }
+            if let Some(continuation) = continuation.take() {
+                params.push(("c", continuation));
+            }
+
+            let page: ItemIds = self.get("stream/items/ids", &params).await?;
+            ids.extend(page.item_refs.into_iter().map(|item| short_id(&item.id)));
+
+            match page.continuation {
+                Some(next) if !next.is_empty() => continuation = Some(next),
+                _ => break,
+            }
+        }
+
+        Ok(ids)
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> AppResult<T> {
+        let response = self
+            .client
+            .get(format!("{}/reader/api/0/{path}", self.url))
+            .header("Authorization", self.authorization())
+            .query(params)
+            .send()
+            .await?;
+        let body = check_status(response)?.text().await?;
+
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    fn authorization(&self) -> String {
+        format!("GoogleLogin auth={}", self.auth)
+    }
+}
+
+fn remote_entry(item: Item) -> RemoteEntry {
+    let link = item
+        .canonical
+        .into_iter()
+        .chain(item.alternate)
+        .next()
+        .map(|link| link.href);
+
+    RemoteEntry {
+        id: short_id(&item.id),
+        feed_id: item.origin.stream_id,
+        title: item
+            .title
+            .filter(|title| !title.is_empty())
+            .or(link.clone())
+            .unwrap_or("Untitled Entry".to_string()),
+        link,
+        author: item.author.filter(|author| !author.is_empty()),
+        content: item.content.or(item.summary).map(|content| content.content),
+        published: item
+            .published
+            .and_then(|published| DateTime::from_timestamp(published, 0)),
+    }
+}
+
+// `tag:google.com,2005:reader/item/000000000000001f` becomes `31`
+pub fn short_id(id: &str) -> String {
+    id.strip_prefix(ITEM_PREFIX)
+        .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+        .map(|id| (id as i64).to_string())
+        .unwrap_or(id.to_string())
+}
+
+pub fn long_id(id: &str) -> String {
+    match id.parse::<i64>() {
+        Ok(id) => format!("{ITEM_PREFIX}{:016x}", id as u64),
+        Err(_) => id.to_string(),
+    }
+}
@@ -0,0 +1,133 @@
+// Commands run for the entries an update brings in, e.g. to post them to a chat:
+//   hooks:
+//     - "curl -s -d @- https://chat.example.com/webhook"
+// `hooks` in the settings run for every new entry, `hooks` in a feed's settings for
+// that feed's entries and a rule's `hook` action for the entries the rule matched.
+// Each command runs once per feed and reads JSON on stdin:
+//   {"feed": {"id", "title", "url"},
+//    "entries": [{"id", "title", "link", "author", "published", "content"}]}
+// Hooks run in the background after the update. A failing hook is logged and
+// holds nothing up.
+
+use std::collections::HashMap;
+
+use chrono::SecondsFormat;
+use log::{debug, warn};
+use serde_json::{json, Value};
+use sqlx::SqliteConnection;
+
+use crate::config::Settings;
+use crate::prelude::InsertedEntry;
+use crate::AppResult;
+
+use super::data::load_export_entry;
+use super::db::{select_all_feed_links, select_entry, select_feed};
+use super::source::execute;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hook {
+    pub command: String,
+    pub payload: String,
+}
+
+// The hooks to run for `inserted`, in the order they are configured
+pub async fn prepare_hooks(
+    conn: &mut SqliteConnection,
+    config: &Settings,
+    inserted: &[InsertedEntry],
+) -> AppResult<Vec<Hook>> {
+    let mut feed_ids: Vec<i64> = vec![];
+    for entry in inserted {
+        if !feed_ids.contains(&entry.feed_id) {
+            feed_ids.push(entry.feed_id);
+        }
+    }
+
+    let mut hooks = vec![];
+
+    for feed_id in feed_ids {
+        let feed = select_feed(conn, &feed_id).await?;
+        let urls = match &feed.url {
+            Some(url) => vec![url.clone()],
+            None => select_all_feed_links(conn, &feed_id)
+                .await?
+                .into_iter()
+                .map(|link| link.href)
+                .collect(),
+        };
+        let feed_hooks: Vec<&String> = urls
+            .iter()
+            .filter_map(|url| config.feed_settings(url))
+            .flat_map(|settings| settings.hooks.iter())
+            .collect();
+
+        // Each command with the entries it runs for
+        let mut commands: Vec<(&String, Vec<i64>)> = vec![];
+        for entry in inserted.iter().filter(|entry| entry.feed_id == feed_id) {
+            for command in config
+                .hooks
+                .iter()
+                .chain(feed_hooks.iter().copied())
+                .chain(entry.hooks.iter())
+            {
+                match commands.iter_mut().find(|(known, _)| *known == command) {
+                    Some((_, ids)) if ids.contains(&entry.id) => {}
+                    Some((_, ids)) => ids.push(entry.id),
+                    None => commands.push((command, vec![entry.id])),
+                }
+            }
+        }
+
+        let mut entries: HashMap<i64, Value> = HashMap::new();
+        for (_, ids) in commands.iter() {
+            for id in ids {
+                if !entries.contains_key(id) {
+                    entries.insert(*id, entry_json(conn, id).await?);
+                }
+            }
+        }
+
+        for (command, ids) in commands {
+            let payload = json!({
+                "feed": {
+                    "id": feed.id,
+                    "title": feed.display_title(),
+                    "url": urls.first(),
+                },
+                "entries": ids.iter().map(|id| entries[id].clone()).collect::<Vec<Value>>(),
+            });
+
+            hooks.push(Hook {
+                command: command.clone(),
+                payload: payload.to_string(),
+            });
+        }
+    }
+
+    Ok(hooks)
+}
+
+pub async fn run_hooks(hooks: Vec<Hook>) {
+    for hook in hooks {
+        match execute(&hook.command, Some(hook.payload.into_bytes())).await {
+            Ok(_) => debug!("Hook `{}` finished", hook.command),
+            Err(e) => warn!("Hook failed: {e}"),
+        }
+    }
+}
+
+async fn entry_json(conn: &mut SqliteConnection, entry_id: &i64) -> AppResult<Value> {
+    let entry = select_entry(conn, entry_id).await?;
+    let export = load_export_entry(conn, &entry).await?;
+
+    Ok(json!({
+        "id": entry.id,
+        "title": export.title,
+        "link": export.link,
+        "author": export.author,
+        "published": export
+            .date
+            .map(|date| date.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true)),
+        "content": export.content,
+    }))
+}
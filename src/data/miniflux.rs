@@ -0,0 +1,228 @@
+// Client for the Miniflux REST API, authenticated with an API token.
+// `url` is where Miniflux is served, the API lives under /v1.
+//
+// Miniflux only toggles the starred flag, so an entry's current flag is looked up
+// before it is changed.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, Method, RequestBuilder};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::AppResult;
+
+use super::sync::{check_status, RemoteEntry, RemoteFeed, RemoteState, SyncAction};
+
+const PAGE_SIZE: usize = 100;
+// At most this many entries are pulled per sync, however far behind the database is
+pub const MAX_ENTRIES: usize = 1000;
+const IDS_PAGE_SIZE: usize = 1000;
+
+pub struct Miniflux {
+    client: Client,
+    url: String,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct Feed {
+    id: i64,
+    title: String,
+    feed_url: String,
+    category: Option<Category>,
+}
+
+#[derive(Deserialize)]
+struct Category {
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct EntryList {
+    #[serde(default)]
+    entries: Vec<Entry>,
+}
+
+#[derive(Deserialize)]
+struct Entry {
+    id: i64,
+    feed_id: i64,
+    title: String,
+    url: Option<String>,
+    author: Option<String>,
+    content: Option<String>,
+    published_at: Option<String>,
+    #[serde(default)]
+    starred: bool,
+}
+
+impl Miniflux {
+    pub fn new(client: Client, url: &str, token: &str) -> Self {
+        Self {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    pub async fn feeds(&self) -> AppResult<Vec<RemoteFeed>> {
+        let feeds: Vec<Feed> = self.get("feeds", &[]).await?;
+
+        Ok(feeds
+            .into_iter()
+            .map(|feed| RemoteFeed {
+                id: feed.id.to_string(),
+                title: feed.title,
+                url: feed.feed_url,
+                folder: feed.category.map(|category| category.title),
+            })
+            .collect())
+    }
+
+    // Newest first, a page at a time until a page holds nothing new
+    pub async fn entries(&self, known: &HashSet<String>) -> AppResult<Vec<RemoteEntry>> {
+        let mut entries = vec![];
+
+        while entries.len() < MAX_ENTRIES {
+            let page: EntryList = self
+                .get(
+                    "entries",
+                    &[
+                        ("order", "id".to_string()),
+                        ("direction", "desc".to_string()),
+                        ("limit", PAGE_SIZE.to_string()),
+                        ("offset", entries.len().to_string()),
+                    ],
+                )
+                .await?;
+
+            let page_entries: Vec<RemoteEntry> =
+                page.entries.into_iter().map(remote_entry).collect();
+            let done = page_entries.len() < PAGE_SIZE
+                || page_entries.iter().all(|entry| known.contains(&entry.id));
+            entries.extend(page_entries);
+
+            if done {
+                break;
+            }
+        }
+
+        entries.truncate(MAX_ENTRIES);
+        Ok(entries)
+    }
+
+    pub async fn state(&self) -> AppResult<RemoteState> {
+        Ok(RemoteState {
+            unread: self.entry_ids(("status", "unread")).await?,
+            starred: self.entry_ids(("starred", "true")).await?,
+        })
+    }
+
+    pub async fn push(&self, action: SyncAction, remote_ids: &[String]) -> AppResult<()> {
+        let ids: Vec<i64> = remote_ids.iter().filter_map(|id| id.parse().ok()).collect();
+
+        match action {
+            SyncAction::Read | SyncAction::Unread => {
+                let status = match action {
+                    SyncAction::Read => "read",
+                    _ => "unread",
+                };
+                let body = json!({ "entry_ids": ids, "status": status });
+
+                check_status(
+                    self.request(Method::PUT, "entries")
+                        .header(CONTENT_TYPE, "application/json")
+                        .body(body.to_string())
+                        .send()
+                        .await?,
+                )?;
+            }
+            SyncAction::Star | SyncAction::Unstar => {
+                let starred = action == SyncAction::Star;
+
+                for id in ids {
+                    let entry: Entry = self.get(&format!("entries/{id}"), &[]).await?;
+                    if entry.starred == starred {
+                        continue;
+                    }
+
+                    check_status(
+                        self.request(Method::PUT, &format!("entries/{id}/bookmark"))
+                            .send()
+                            .await?,
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Every matching id, entries missing from a partial list would be taken as read
+    async fn entry_ids(&self, filter: (&str, &str)) -> AppResult<HashSet<String>> {
+        let mut ids = HashSet::new();
+        let mut offset = 0;
+
+        loop {
+            let page: EntryList = self
+                .get(
+                    "entries",
+                    &[
+                        (filter.0, filter.1.to_string()),
+                        ("order", "id".to_string()),
+                        ("limit", IDS_PAGE_SIZE.to_string()),
+                        ("offset", offset.to_string()),
+                    ],
+                )
+                .await?;
+
+            let len = page.entries.len();
+            ids.extend(page.entries.into_iter().map(|entry| entry.id.to_string()));
+            offset += len;
+
+            if len < IDS_PAGE_SIZE {
+                break;
+            }
+        }
+
+        Ok(ids)
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> AppResult<T> {
+        let response = self.request(Method::GET, path).query(params).send().await?;
+        let body = check_status(response)?.text().await?;
+
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}/v1/{path}", self.url))
+            .header("X-Auth-Token", &self.token)
+    }
+}
+
+fn remote_entry(entry: Entry) -> RemoteEntry {
+    RemoteEntry {
+        id: entry.id.to_string(),
+        feed_id: entry.feed_id.to_string(),
+        title: Some(entry.title)
+            .filter(|title| !title.is_empty())
+            .or(entry.url.clone())
+            .unwrap_or("Untitled Entry".to_string()),
+        link: entry.url.filter(|url| !url.is_empty()),
+        author: entry.author.filter(|author| !author.is_empty()),
+        content: entry.content.filter(|content| !content.is_empty()),
+        published: entry
+            .published_at
+            .and_then(|date| DateTime::parse_from_rfc3339(&date).ok())
+            .map(|date| date.with_timezone(&Utc)),
+    }
+}
@@ -1,2 +1,14 @@
+pub mod article;
 pub mod data;
 pub mod db;
+pub mod duplicates;
+pub mod encoding;
+pub mod fetch;
+pub mod greader;
+pub mod hooks;
+pub mod miniflux;
+pub mod query;
+pub mod rules;
+pub mod source;
+pub mod stats;
+pub mod sync;
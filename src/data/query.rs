@@ -0,0 +1,178 @@
+// Compiles smart folder queries such as `unread feed:"Rust Blog" title:~async newer:7d`
+// into a WHERE clause over the entry table.
+//
+// Terms are joined with AND and may be negated with a leading `-`:
+//   unread, read, starred            entry state
+//   feed: title: content:            feed title, entry title, entry summary or content
+//   folder:                          folder of the feed on the sync server
+//   author: category: tag:           related rows
+//   newer:7d older:12h               entry age in hours (h), days (d) or weeks (w)
+//   any other word                   entry title contains the word
+// Values starting with `~` match anywhere in the text, otherwise the whole text must
+// match, ignoring case. Values containing spaces can be quoted.
+
+use std::str::FromStr;
+
+use crate::error::Error;
+use crate::AppResult;
+use chrono::{Duration, NaiveDateTime, Utc};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryParam {
+    Text(String),
+    Date(NaiveDateTime),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryQuery {
+    pub filter: String,
+    pub params: Vec<QueryParam>,
+}
+
+impl FromStr for EntryQuery {
+    type Err = Error;
+
+    fn from_str(query: &str) -> AppResult<Self> {
+        let mut conditions = vec!["(entry.hidden IS NULL OR entry.hidden = FALSE)".to_string()];
+        let mut params = vec![];
+
+        for token in tokenize(query)? {
+            let (negated, term) = match token.strip_prefix('-') {
+                Some(term) if !term.is_empty() => (true, term.to_string()),
+                _ => (false, token),
+            };
+
+            let condition = compile_term(&term, &mut params)?;
+
+            if negated {
+                conditions.push(format!("NOT ({condition})"));
+            } else {
+                conditions.push(condition);
+            }
+        }
+
+        Ok(Self {
+            filter: conditions.join(" AND "),
+            params,
+        })
+    }
+}
+
+fn compile_term(term: &str, params: &mut Vec<QueryParam>) -> AppResult<String> {
+    let Some((key, value)) = term.split_once(':') else {
+        return Ok(match term {
+            "unread" => "(entry.read IS NULL OR entry.read = FALSE)".to_string(),
+            "read" => "entry.read = TRUE".to_string(),
+            "starred" => "entry.starred = TRUE".to_string(),
+            word => {
+                params.push(QueryParam::Text(format!("%{word}%")));
+                "entry.title LIKE ?".to_string()
+            }
+        });
+    };
+
+    if value.is_empty() {
+        return Err(Error::Generic(format!("Missing value for {key}:")));
+    }
+
+    let condition = match key {
+        // The title shown in the UI, a renamed feed goes by its new name
+        "feed" => format!(
+            "entry.feed_id IN (SELECT feed.id FROM feed WHERE {})",
+            text_match("COALESCE(feed.custom_title, feed.title)", value, params)
+        ),
+        "folder" => format!(
+            "entry.feed_id IN (SELECT feed.id FROM feed WHERE {})",
+            text_match("feed.folder", value, params)
+        ),
+        "title" => text_match("entry.title", value, params),
+        "content" => format!(
+            "({} OR entry.content_id IN (SELECT content.id FROM content WHERE {}))",
+            text_match("entry.summary", value, params),
+            text_match("content.body", value, params)
+        ),
+        "author" => format!(
+            "entry.id IN (SELECT entry_author.entry_id FROM entry_author \
+            JOIN author ON author.id = entry_author.author_id WHERE {})",
+            text_match("author.name", value, params)
+        ),
+        "category" => format!(
+            "entry.id IN (SELECT entry_category.entry_id FROM entry_category \
+            JOIN category ON category.id = entry_category.category_id WHERE {})",
+            text_match("category.term", value, params)
+        ),
+        "tag" => format!(
+            "entry.id IN (SELECT entry_tag.entry_id FROM entry_tag \
+            JOIN tag ON tag.id = entry_tag.tag_id WHERE {})",
+            text_match("tag.name", value, params)
+        ),
+        "newer" => {
+            params.push(QueryParam::Date(Utc::now().naive_utc() - parse_age(value)?));
+            "entry.updated >= ?".to_string()
+        }
+        "older" => {
+            params.push(QueryParam::Date(Utc::now().naive_utc() - parse_age(value)?));
+            "entry.updated < ?".to_string()
+        }
+        _ => return Err(Error::Generic(format!("Unknown search term {key}:"))),
+    };
+
+    Ok(condition)
+}
+
+fn text_match(column: &str, value: &str, params: &mut Vec<QueryParam>) -> String {
+    match value.strip_prefix('~') {
+        Some(fragment) => {
+            params.push(QueryParam::Text(format!("%{fragment}%")));
+            format!("{column} LIKE ?")
+        }
+        None => {
+            params.push(QueryParam::Text(value.to_string()));
+            format!("{column} = ? COLLATE NOCASE")
+        }
+    }
+}
+
+fn parse_age(value: &str) -> AppResult<Duration> {
+    let invalid = || Error::Generic(format!("Invalid age {value}, expected e.g. 7d"));
+
+    let split = value.len() - value.chars().last().map(|c| c.len_utf8()).unwrap_or(0);
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+
+    match unit {
+        "h" => Ok(Duration::hours(amount)),
+        "d" => Ok(Duration::days(amount)),
+        "w" => Ok(Duration::weeks(amount)),
+        _ => Err(invalid()),
+    }
+}
+
+fn tokenize(query: &str) -> AppResult<Vec<String>> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(current.clone());
+                    current.clear();
+                }
+            }
+            c => current.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(Error::Static("Unterminated quote in query"));
+    }
+
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    Ok(tokens)
+}
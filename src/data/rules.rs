@@ -0,0 +1,90 @@
+// Decides which rule actions apply to an incoming entry
+
+use crate::config::{Pattern, Rule, RuleAction};
+use feed_rs::model;
+
+pub struct RuleFeed<'a> {
+    pub title: Option<&'a str>,
+    pub urls: Vec<&'a str>,
+}
+
+pub fn rule_actions(rules: &[Rule], feed: &RuleFeed, entry: &model::Entry) -> Vec<RuleAction> {
+    let mut actions = vec![];
+
+    for rule in rules.iter().filter(|rule| rule_matches(rule, feed, entry)) {
+        for action in rule.actions.iter() {
+            if !actions.contains(action) {
+                actions.push(action.clone());
+            }
+        }
+    }
+
+    actions
+}
+
+pub fn rule_matches(rule: &Rule, feed: &RuleFeed, entry: &model::Entry) -> bool {
+    // A rule without conditions would match everything, which is never what was meant
+    if rule.feed.is_none()
+        && rule.title.is_none()
+        && rule.content.is_none()
+        && rule.author.is_none()
+        && rule.category.is_none()
+    {
+        return false;
+    }
+
+    if let Some(wanted) = &rule.feed {
+        let title_matches = feed
+            .title
+            .is_some_and(|title| title.eq_ignore_ascii_case(wanted));
+        let url_matches = feed.urls.iter().any(|url| url == wanted);
+
+        if !title_matches && !url_matches {
+            return false;
+        }
+    }
+
+    if let Some(pattern) = &rule.title {
+        let title = entry.title.as_ref().map(|t| t.content.as_str());
+        if !regex_matches(pattern, title) {
+            return false;
+        }
+    }
+
+    if let Some(pattern) = &rule.content {
+        let content = entry.content.as_ref().and_then(|c| c.body.as_deref());
+        let summary = entry.summary.as_ref().map(|s| s.content.as_str());
+
+        if !regex_matches(pattern, content) && !regex_matches(pattern, summary) {
+            return false;
+        }
+    }
+
+    if let Some(wanted) = &rule.author {
+        if !entry
+            .authors
+            .iter()
+            .any(|author| author.name.eq_ignore_ascii_case(wanted))
+        {
+            return false;
+        }
+    }
+
+    if let Some(wanted) = &rule.category {
+        if !entry.categories.iter().any(|category| {
+            category.term.eq_ignore_ascii_case(wanted)
+                || category
+                    .label
+                    .as_ref()
+                    .is_some_and(|label| label.eq_ignore_ascii_case(wanted))
+        }) {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn regex_matches(pattern: &Pattern, text: Option<&str>) -> bool {
+    text.is_some_and(|text| pattern.is_match(text))
+}
@@ -0,0 +1,160 @@
+// Where a feed's document comes from. Besides http(s), a subscription url can
+// point at a local file or a command whose output is the feed:
+//   file:///home/me/feeds/generated.xml
+//   exec:ssh server cat /var/www/feed.xml
+// A document piped into `crabfeed import` is stored once instead, see `data::import_feed`.
+
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use crate::config::Settings;
+use crate::error::Error;
+use crate::AppResult;
+use reqwest::Url;
+use tokio::io::AsyncWriteExt;
+
+use super::encoding::decode_feed;
+use super::fetch::{content_charset, fetch_authenticated, read_body, Credentials, HttpClients};
+
+pub const COMMAND_PREFIX: &str = "exec:";
+const COMMAND_TIMEOUT: u64 = 60;
+
+#[derive(Debug, PartialEq)]
+pub enum FeedSource {
+    Http(String),
+    File(PathBuf),
+    Command(String),
+}
+
+impl FeedSource {
+    pub fn parse(url: &str) -> AppResult<Self> {
+        if let Some(command) = url.strip_prefix(COMMAND_PREFIX) {
+            let command = command.trim();
+            if command.is_empty() {
+                return Err(Error::Generic(format!("No command given in {url}")));
+            }
+            return Ok(FeedSource::Command(command.to_string()));
+        }
+
+        if url.starts_with("file:") {
+            return Url::parse(url)
+                .ok()
+                .and_then(|url| url.to_file_path().ok())
+                .map(FeedSource::File)
+                .ok_or_else(|| Error::Generic(format!("Invalid file url {url}")));
+        }
+
+        Ok(FeedSource::Http(url.to_string()))
+    }
+}
+
+pub struct Download {
+    // The document as UTF-8
+    pub body: Vec<u8>,
+    // Where the feed now lives if every redirect on the way was permanent
+    pub moved_to: Option<String>,
+}
+
+// Reads a feed from wherever its url points, with the client and credentials
+// configured for it when it is fetched over http. A configured filter gets the
+// raw document and its output is what gets decoded to UTF-8 and parsed.
+pub async fn download(config: &Settings, clients: &HttpClients, url: &str) -> AppResult<Download> {
+    let (mut body, charset, moved_to) = match FeedSource::parse(url)? {
+        FeedSource::Http(url) => {
+            let credentials = match config
+                .feed_settings(&url)
+                .and_then(|settings| settings.auth.as_ref())
+            {
+                Some(auth) => Some(Credentials::resolve(auth).await?),
+                None => None,
+            };
+
+            let fetched =
+                fetch_authenticated(clients.client(&url), &url, credentials.as_ref()).await?;
+            let charset = content_charset(&fetched.response);
+            let body = read_body(fetched.response, clients.limits(&url)).await?;
+
+            (body, charset, fetched.moved_to)
+        }
+        FeedSource::File(path) => (tokio::fs::read(&path).await?, None, None),
+        FeedSource::Command(command) => (run_command(&command, None).await?, None, None),
+    };
+
+    if let Some(filter) = config
+        .feed_settings(url)
+        .and_then(|settings| settings.filter.as_ref())
+    {
+        body = filter_feed(filter, body).await?;
+    }
+
+    Ok(Download {
+        body: decode_feed(body, charset.as_deref()),
+        moved_to,
+    })
+}
+
+pub async fn filter_feed(filter: &str, body: Vec<u8>) -> AppResult<Vec<u8>> {
+    run_command(filter, Some(body)).await.map_err(|e| match e {
+        Error::Command(reason) => Error::Command(format!("filter {reason}")),
+        e => e,
+    })
+}
+
+// Like `execute`, for commands whose output is a document
+async fn run_command(command: &str, input: Option<Vec<u8>>) -> AppResult<Vec<u8>> {
+    let output = execute(command, input).await?;
+
+    if output.is_empty() {
+        return Err(Error::Command(format!("`{command}` printed nothing")));
+    }
+
+    Ok(output)
+}
+
+// Runs a shell command and returns what it printed, with `input` written to its stdin
+pub(crate) async fn execute(command: &str, input: Option<Vec<u8>>) -> AppResult<Vec<u8>> {
+    let mut child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(match input {
+            Some(_) => Stdio::piped(),
+            None => Stdio::null(),
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    // Written from its own task so a command that prints before reading all of
+    // its input can't block on a full pipe
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        tokio::spawn(async move {
+            // A command may exit without reading everything, that is up to it
+            let _ = stdin.write_all(&input).await;
+        });
+    }
+
+    let output = tokio::time::timeout(
+        Duration::from_secs(COMMAND_TIMEOUT),
+        child.wait_with_output(),
+    )
+    .await
+    .map_err(|_| {
+        Error::Command(format!(
+            "`{command}` timed out after {COMMAND_TIMEOUT} seconds"
+        ))
+    })??;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Command(match stderr.lines().next() {
+            Some(reason) if !reason.trim().is_empty() => {
+                format!("`{command}` failed ({}): {}", output.status, reason.trim())
+            }
+            _ => format!("`{command}` failed ({})", output.status),
+        }));
+    }
+
+    Ok(output.stdout)
+}
@@ -0,0 +1,145 @@
+// Reading statistics for the dashboard, built from aggregate queries over the feeds
+// and their entries. Weeks count back from the moment the statistics are taken.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDateTime};
+use sqlx::SqliteConnection;
+
+use crate::AppResult;
+
+use super::db::{
+    select_all_feeds, select_database_size, select_feed_entry_counts, select_weekly_entry_counts,
+};
+
+pub const WEEKS: usize = 12;
+// Feeds without an entry for this many days are listed as quiet, unless set otherwise
+pub const DEFAULT_STALE_DAYS: i64 = 30;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FeedStats {
+    pub feed_id: i64,
+    pub title: String,
+    pub entries: u64,
+    pub read: u64,
+    pub last_entry: Option<NaiveDateTime>,
+    // Entries per week, oldest first, the last is the current week
+    pub weekly: Vec<u64>,
+}
+
+impl FeedStats {
+    pub fn read_ratio(&self) -> f64 {
+        ratio(self.read, self.entries)
+    }
+
+    pub fn recent(&self) -> u64 {
+        self.weekly.iter().sum()
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Statistics {
+    pub feeds: Vec<FeedStats>,
+    pub database_size: u64,
+    pub taken: NaiveDateTime,
+    pub stale_days: i64,
+}
+
+impl Statistics {
+    pub fn entries(&self) -> u64 {
+        self.feeds.iter().map(|feed| feed.entries).sum()
+    }
+
+    pub fn read_ratio(&self) -> f64 {
+        ratio(
+            self.feeds.iter().map(|feed| feed.read).sum(),
+            self.entries(),
+        )
+    }
+
+    // Entries per week over all feeds, oldest first
+    pub fn weekly(&self) -> Vec<u64> {
+        (0..WEEKS)
+            .map(|week| {
+                self.feeds
+                    .iter()
+                    .map(|feed| feed.weekly.get(week).copied().unwrap_or_default())
+                    .sum()
+            })
+            .collect()
+    }
+
+    // The feeds with the most entries over the last WEEKS weeks
+    pub fn most_active(&self, count: usize) -> Vec<&FeedStats> {
+        let mut feeds: Vec<&FeedStats> =
+            self.feeds.iter().filter(|feed| feed.recent() > 0).collect();
+        feeds.sort_by(|a, b| b.recent().cmp(&a.recent()).then(a.title.cmp(&b.title)));
+        feeds.truncate(count);
+        feeds
+    }
+
+    // Feeds with nothing new for `stale_days`, the quietest first
+    pub fn stale(&self) -> Vec<&FeedStats> {
+        let since = self.taken - Duration::days(self.stale_days);
+        let mut feeds: Vec<&FeedStats> = self
+            .feeds
+            .iter()
+            .filter(|feed| feed.last_entry.is_none_or(|last| last < since))
+            .collect();
+        feeds.sort_by(|a, b| a.last_entry.cmp(&b.last_entry).then(a.title.cmp(&b.title)));
+        feeds
+    }
+}
+
+pub async fn statistics(
+    conn: &mut SqliteConnection,
+    now: NaiveDateTime,
+    stale_days: i64,
+) -> AppResult<Statistics> {
+    let titles: HashMap<i64, String> = select_all_feeds(conn)
+        .await?
+        .into_iter()
+        .map(|feed| {
+            let title = feed.display_title().unwrap_or("Untitled Feed".to_string());
+            (feed.id, title)
+        })
+        .collect();
+
+    let mut feeds: Vec<FeedStats> = select_feed_entry_counts(conn)
+        .await?
+        .into_iter()
+        .map(|(feed_id, entries, read, last_entry)| FeedStats {
+            feed_id,
+            title: titles.get(&feed_id).cloned().unwrap_or_default(),
+            entries: entries as u64,
+            read: read as u64,
+            last_entry,
+            weekly: vec![0; WEEKS],
+        })
+        .collect();
+
+    for (feed_id, week, count) in select_weekly_entry_counts(conn, now, WEEKS as i64).await? {
+        let Some(feed) = feeds.iter_mut().find(|feed| feed.feed_id == feed_id) else {
+            continue;
+        };
+        if (0..WEEKS as i64).contains(&week) {
+            feed.weekly[WEEKS - 1 - week as usize] = count as u64;
+        }
+    }
+
+    feeds.sort_by_key(|feed| feed.title.to_lowercase());
+
+    Ok(Statistics {
+        feeds,
+        database_size: select_database_size(conn).await? as u64,
+        taken: now,
+        stale_days,
+    })
+}
+
+fn ratio(part: u64, whole: u64) -> f64 {
+    match whole {
+        0 => 0.0,
+        whole => part as f64 / whole as f64,
+    }
+}
@@ -0,0 +1,350 @@
+// Keeps the database in step with an account on a sync server. A sync first pushes
+// the queued read and star changes, then pulls the subscriptions with their folders,
+// the new entries and finally the read and starred state of every synced entry.
+// Changes made while the server can't be reached wait in the queue for the next sync.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use feed_rs::model;
+use reqwest::{Client, Response, StatusCode};
+use sqlx::SqliteConnection;
+
+use crate::config::{Rule, SyncSettings};
+use crate::error::Error;
+use crate::prelude::InsertedEntry;
+use crate::AppResult;
+
+use super::db::{
+    delete_feed, delete_sync_change, delete_sync_feed, insert_entries, insert_remote_feed,
+    insert_sync_change, insert_sync_entry, insert_sync_feed, mark_entry_starred,
+    select_entry_by_title, select_feed_by_url, select_sync_entries, select_sync_feeds,
+    select_sync_queue, update_entry_read, update_feed_folder,
+};
+use super::fetch::resolve_secret;
+use super::greader::GoogleReader;
+use super::miniflux::Miniflux;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncAction {
+    Read,
+    Unread,
+    Star,
+    Unstar,
+}
+
+impl SyncAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncAction::Read => "read",
+            SyncAction::Unread => "unread",
+            SyncAction::Star => "star",
+            SyncAction::Unstar => "unstar",
+        }
+    }
+
+    fn opposite(&self) -> SyncAction {
+        match self {
+            SyncAction::Read => SyncAction::Unread,
+            SyncAction::Unread => SyncAction::Read,
+            SyncAction::Star => SyncAction::Unstar,
+            SyncAction::Unstar => SyncAction::Star,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RemoteFeed {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub folder: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RemoteEntry {
+    pub id: String,
+    pub feed_id: String,
+    pub title: String,
+    pub link: Option<String>,
+    pub author: Option<String>,
+    pub content: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+}
+
+impl RemoteEntry {
+    // Shaped like a parsed feed entry so it goes through the usual insert path and rules
+    pub fn to_model(&self) -> model::Entry {
+        let mut entry = model::Entry {
+            id: self.id.clone(),
+            title: Some(model::Text {
+                content_type: mime::TEXT_PLAIN,
+                src: None,
+                content: self.title.clone(),
+            }),
+            updated: self.published,
+            published: self.published,
+            ..Default::default()
+        };
+
+        if let Some(link) = &self.link {
+            entry.links.push(model::Link {
+                href: link.clone(),
+                rel: None,
+                media_type: None,
+                href_lang: None,
+                title: None,
+                length: None,
+            });
+        }
+
+        if let Some(author) = &self.author {
+            entry.authors.push(model::Person {
+                name: author.clone(),
+                uri: None,
+                email: None,
+            });
+        }
+
+        entry.content = self.content.as_ref().map(|body| model::Content {
+            body: Some(body.clone()),
+            content_type: mime::TEXT_HTML,
+            length: None,
+            src: None,
+        });
+
+        entry
+    }
+}
+
+// Remote ids of the entries the server has as unread and as starred
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RemoteState {
+    pub unread: HashSet<String>,
+    pub starred: HashSet<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SyncReport {
+    pub pushed: usize,
+    pub feeds: usize,
+    pub entries: usize,
+    // Entries that were not in the database before
+    pub new_entries: Vec<InsertedEntry>,
+}
+
+pub enum SyncBackend {
+    GoogleReader(GoogleReader),
+    Miniflux(Miniflux),
+}
+
+impl SyncBackend {
+    pub async fn connect(settings: &SyncSettings, client: Client) -> AppResult<Self> {
+        match settings {
+            SyncSettings::GoogleReader {
+                url,
+                username,
+                password,
+            } => {
+                let password = resolve_secret(password).await?;
+                Ok(SyncBackend::GoogleReader(
+                    GoogleReader::login(client, url, username, &password).await?,
+                ))
+            }
+            SyncSettings::Miniflux { url, token } => {
+                let token = resolve_secret(token).await?;
+                Ok(SyncBackend::Miniflux(Miniflux::new(client, url, &token)))
+            }
+        }
+    }
+
+    async fn push(&mut self, action: SyncAction, remote_ids: &[String]) -> AppResult<()> {
+        match self {
+            SyncBackend::GoogleReader(reader) => reader.push(action, remote_ids).await,
+            SyncBackend::Miniflux(miniflux) => miniflux.push(action, remote_ids).await,
+        }
+    }
+
+    async fn feeds(&self) -> AppResult<Vec<RemoteFeed>> {
+        match self {
+            SyncBackend::GoogleReader(reader) => reader.subscriptions().await,
+            SyncBackend::Miniflux(miniflux) => miniflux.feeds().await,
+        }
+    }
+
+    // Newest first, stopping once the entries are already known
+    async fn entries(&self, known: &HashSet<String>) -> AppResult<Vec<RemoteEntry>> {
+        match self {
+            SyncBackend::GoogleReader(reader) => reader.entries(known).await,
+            SyncBackend::Miniflux(miniflux) => miniflux.entries(known).await,
+        }
+    }
+
+    async fn state(&self) -> AppResult<RemoteState> {
+        match self {
+            SyncBackend::GoogleReader(reader) => reader.state().await,
+            SyncBackend::Miniflux(miniflux) => miniflux.state().await,
+        }
+    }
+}
+
+// Records a local read or star change to be pushed with the next sync
+pub async fn queue_change(
+    conn: &mut SqliteConnection,
+    entry_id: &i64,
+    action: SyncAction,
+) -> AppResult<()> {
+    insert_sync_change(
+        conn,
+        entry_id,
+        action.as_str(),
+        &[action.as_str(), action.opposite().as_str()],
+    )
+    .await
+}
+
+pub async fn sync(
+    conn: &mut SqliteConnection,
+    backend: &mut SyncBackend,
+    rules: &[Rule],
+) -> AppResult<SyncReport> {
+    // Pushed first so the state pulled afterwards already includes local changes
+    let pushed = push_changes(conn, backend).await?;
+
+    let remote_feeds = backend.feeds().await?;
+    let mut feed_ids: HashMap<String, i64> = HashMap::new();
+
+    for feed in select_sync_feeds(conn).await? {
+        if remote_feeds
+            .iter()
+            .any(|remote| remote.id == feed.remote_id)
+        {
+            feed_ids.insert(feed.remote_id, feed.local_id);
+            continue;
+        }
+
+        // Unsubscribed on the server, maybe from another device. A feed that was
+        // here before the sync found it stays, it is only fetched directly again.
+        match feed.created {
+            true => delete_feed(conn, feed.local_id).await?,
+            false => delete_sync_feed(conn, &feed.local_id).await?,
+        }
+    }
+
+    for feed in remote_feeds.iter() {
+        let feed_id = match feed_ids.get(&feed.id) {
+            Some(feed_id) => *feed_id,
+            None => {
+                let (feed_id, created) = match select_feed_by_url(conn, &feed.url).await? {
+                    Some(local) => (local.id, false),
+                    None => (
+                        insert_remote_feed(conn, &feed.title, &feed.url).await?,
+                        true,
+                    ),
+                };
+                insert_sync_feed(conn, &feed_id, &feed.id, created).await?;
+                feed_ids.insert(feed.id.clone(), feed_id);
+                feed_id
+            }
+        };
+
+        update_feed_folder(conn, &feed_id, feed.folder.clone()).await?;
+    }
+
+    let known: HashSet<String> = select_sync_entries(conn)
+        .await?
+        .into_iter()
+        .map(|entry| entry.remote_id)
+        .collect();
+    let mut inserted = 0;
+    let mut new_entries = vec![];
+
+    for entry in backend.entries(&known).await? {
+        if known.contains(&entry.id) {
+            continue;
+        }
+        let Some(feed_id) = feed_ids.get(&entry.feed_id) else {
+            continue;
+        };
+
+        let added = insert_entries(conn, vec![entry.to_model()], *feed_id, rules).await?;
+        let local_id = match added.first() {
+            Some(added) => Some(added.id),
+            // Already there, e.g. fetched directly before the feed was synced
+            None => select_entry_by_title(conn, feed_id, &entry.title)
+                .await?
+                .map(|local| local.id),
+        };
+        new_entries.extend(added);
+
+        if let Some(local_id) = local_id {
+            if insert_sync_entry(conn, &local_id, &entry.id).await? {
+                inserted += 1;
+            }
+        }
+    }
+
+    let state = backend.state().await?;
+    for entry in select_sync_entries(conn).await? {
+        let read = !state.unread.contains(&entry.remote_id);
+        let starred = state.starred.contains(&entry.remote_id);
+
+        update_entry_read(conn, &entry.local_id, read).await?;
+        mark_entry_starred(conn, &entry.local_id, starred).await?;
+    }
+
+    Ok(SyncReport {
+        pushed,
+        feeds: remote_feeds.len(),
+        entries: inserted,
+        new_entries,
+    })
+}
+
+// Sends the queue one action at a time. Whatever the server accepted is dropped from
+// the queue, the rest stays for the next sync.
+async fn push_changes(conn: &mut SqliteConnection, backend: &mut SyncBackend) -> AppResult<usize> {
+    let queue = select_sync_queue(conn).await?;
+    let mut pushed = 0;
+
+    for action in [
+        SyncAction::Read,
+        SyncAction::Unread,
+        SyncAction::Star,
+        SyncAction::Unstar,
+    ] {
+        let changes: Vec<_> = queue
+            .iter()
+            .filter(|change| change.action == action.as_str())
+            .collect();
+        if changes.is_empty() {
+            continue;
+        }
+
+        let remote_ids: Vec<String> = changes
+            .iter()
+            .map(|change| change.remote_id.clone())
+            .collect();
+        backend.push(action, &remote_ids).await?;
+
+        for change in changes {
+            delete_sync_change(conn, &change.id).await?;
+        }
+        pushed += remote_ids.len();
+    }
+
+    Ok(pushed)
+}
+
+pub(crate) fn check_status(response: Response) -> AppResult<Response> {
+    let status = response.status();
+
+    if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+        return Err(Error::Unauthorized(status));
+    }
+    if !status.is_success() {
+        return Err(Error::HttpStatus(status));
+    }
+
+    Ok(response)
+}
@@ -34,6 +34,27 @@ pub enum Error {
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
 
+    #[error("Authentication failed ({0})")]
+    Unauthorized(reqwest::StatusCode),
+
+    #[error("Server responded with {0}")]
+    HttpStatus(reqwest::StatusCode),
+
+    #[error("Response is larger than {0} bytes")]
+    ResponseTooLarge(u64),
+
+    #[error("No data received for {0} seconds")]
+    ReadTimeout(u64),
+
+    #[error("Could not read secret: {0}")]
+    Secret(String),
+
+    #[error("Command failed: {0}")]
+    Command(String),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
     #[error(transparent)]
     FeedParsing(#[from] feed_rs::parser::ParseFeedError),
 
@@ -45,4 +66,7 @@ pub enum Error {
 
     #[error(transparent)]
     ConfigurationError(#[from] config::ConfigError),
+
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
 }
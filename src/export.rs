@@ -0,0 +1,393 @@
+// Writes entries out as Markdown, a standalone HTML page or an EPUB book
+
+use std::fs::{create_dir_all, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{NaiveDateTime, Utc};
+use directories::UserDirs;
+use html_parser::{Dom, Node};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::error::Error;
+use crate::AppResult;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+    Epub,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+            ExportFormat::Epub => "epub",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = Error;
+
+    fn from_str(format: &str) -> AppResult<Self> {
+        match format.to_lowercase().as_str() {
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            "html" => Ok(ExportFormat::Html),
+            "epub" => Ok(ExportFormat::Epub),
+            _ => Err(Error::Generic(format!("Unknown export format {format}"))),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ExportEntry {
+    pub title: String,
+    pub feed: String,
+    pub author: Option<String>,
+    pub date: Option<NaiveDateTime>,
+    pub link: Option<String>,
+    pub content: String,
+}
+
+// Exports land in the download directory unless a path is given
+pub fn default_export_path(title: &str, format: ExportFormat) -> PathBuf {
+    let dir = UserDirs::new()
+        .and_then(|dirs| dirs.download_dir().map(|dir| dir.to_path_buf()))
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    dir.join(format!("{}.{}", file_name(title), format.extension()))
+}
+
+pub fn export(
+    path: &Path,
+    format: ExportFormat,
+    title: &str,
+    entries: &[ExportEntry],
+) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            create_dir_all(parent)?;
+        }
+    }
+
+    match format {
+        ExportFormat::Markdown => {
+            File::create(path)?.write_all(render_markdown(entries).as_bytes())?;
+        }
+        ExportFormat::Html => {
+            File::create(path)?.write_all(render_html(title, entries).as_bytes())?;
+        }
+        ExportFormat::Epub => write_epub(path, title, entries)?,
+    }
+
+    Ok(())
+}
+
+pub fn render_markdown(entries: &[ExportEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            let mut page = format!("# {}\n\n", entry.title);
+
+            for (label, value) in metadata(entry) {
+                page.push_str(&format!("- **{label}:** {value}\n"));
+            }
+
+            page.push('\n');
+            page.push_str(&html2text::from_read(entry.content.as_bytes(), 80));
+            page
+        })
+        .collect::<Vec<String>>()
+        .join("\n---\n\n")
+}
+
+pub fn render_html(title: &str, entries: &[ExportEntry]) -> String {
+    let articles = entries
+        .iter()
+        .map(|entry| format!("<article>\n{}\n</article>", entry_xhtml(entry)))
+        .collect::<Vec<String>>()
+        .join("\n<hr/>\n");
+
+    format!(
+        "<!DOCTYPE html>\n\
+        <html>\n\
+        <head>\n\
+        <meta charset=\"utf-8\"/>\n\
+        <title>{}</title>\n\
+        <style>body {{ max-width: 45em; margin: auto; font-family: serif; }}</style>\n\
+        </head>\n\
+        <body>\n{}\n</body>\n\
+        </html>\n",
+        escape(title),
+        articles
+    )
+}
+
+fn write_epub(path: &Path, title: &str, entries: &[ExportEntry]) -> AppResult<()> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+
+    // The mimetype has to come first and be stored uncompressed
+    zip.start_file(
+        "mimetype",
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+    )?;
+    zip.write_all(b"application/epub+zip")?;
+
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("META-INF/container.xml", options)?;
+    zip.write_all(
+        b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n\
+        <rootfiles>\n\
+        <rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\"/>\n\
+        </rootfiles>\n\
+        </container>\n",
+    )?;
+
+    let mut manifest = String::new();
+    let mut spine = String::new();
+    let mut toc = String::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let name = format!("chapter_{index}.xhtml");
+
+        zip.start_file(format!("OEBPS/{name}"), options)?;
+        zip.write_all(xhtml_page(&entry.title, &entry_xhtml(entry)).as_bytes())?;
+
+        manifest.push_str(&format!(
+            "<item id=\"chapter_{index}\" href=\"{name}\" media-type=\"application/xhtml+xml\"/>\n"
+        ));
+        spine.push_str(&format!("<itemref idref=\"chapter_{index}\"/>\n"));
+        toc.push_str(&format!(
+            "<li><a href=\"{name}\">{}</a></li>\n",
+            escape(&entry.title)
+        ));
+    }
+
+    zip.start_file("OEBPS/nav.xhtml", options)?;
+    zip.write_all(
+        xhtml_page(
+            title,
+            &format!(
+                "<nav epub:type=\"toc\">\n<h1>{}</h1>\n<ol>\n{toc}</ol>\n</nav>",
+                escape(title)
+            ),
+        )
+        .as_bytes(),
+    )?;
+
+    let modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+    let identifier = Utc::now().timestamp_millis();
+
+    zip.start_file("OEBPS/content.opf", options)?;
+    zip.write_all(
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"id\">\n\
+            <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+            <dc:identifier id=\"id\">crabfeed-{identifier}</dc:identifier>\n\
+            <dc:title>{}</dc:title>\n\
+            <dc:language>en</dc:language>\n\
+            <meta property=\"dcterms:modified\">{modified}</meta>\n\
+            </metadata>\n\
+            <manifest>\n\
+            <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n\
+            {manifest}\
+            </manifest>\n\
+            <spine>\n{spine}</spine>\n\
+            </package>\n",
+            escape(title)
+        )
+        .as_bytes(),
+    )?;
+
+    zip.finish()?;
+
+    Ok(())
+}
+
+fn xhtml_page(title: &str, body: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <!DOCTYPE html>\n\
+        <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\">\n\
+        <head><title>{}</title></head>\n\
+        <body>\n{body}\n</body>\n\
+        </html>\n",
+        escape(title)
+    )
+}
+
+fn metadata(entry: &ExportEntry) -> Vec<(&'static str, String)> {
+    let mut fields = vec![("Feed", entry.feed.clone())];
+
+    if let Some(author) = &entry.author {
+        fields.push(("Author", author.clone()));
+    }
+    if let Some(date) = &entry.date {
+        fields.push(("Date", date.format("%Y-%m-%d %H:%M").to_string()));
+    }
+    if let Some(link) = &entry.link {
+        fields.push(("Source", link.clone()));
+    }
+
+    fields
+}
+
+fn entry_xhtml(entry: &ExportEntry) -> String {
+    let mut page = format!("<h1>{}</h1>\n<ul>\n", escape(&entry.title));
+
+    for (label, value) in metadata(entry) {
+        let value = match label {
+            "Source" => format!("<a href=\"{0}\">{0}</a>", escape(&value)),
+            _ => escape(&value),
+        };
+        page.push_str(&format!("<li><b>{label}:</b> {value}</li>\n"));
+    }
+
+    page.push_str("</ul>\n");
+    page.push_str(&to_xhtml(&entry.content));
+    page
+}
+
+// Rebuilds feed HTML as well-formed XHTML, keeping only simple formatting tags
+pub fn to_xhtml(html: &str) -> String {
+    let Ok(dom) = Dom::parse(html) else {
+        return format!("<p>{}</p>", escape(&decode_entities(html)));
+    };
+
+    let mut out = String::new();
+    for node in dom.children.iter() {
+        write_xhtml(node, &mut out);
+    }
+    out
+}
+
+fn write_xhtml(node: &Node, out: &mut String) {
+    match node {
+        Node::Text(text) => out.push_str(&escape(&decode_entities(text))),
+        Node::Comment(_) => {}
+        Node::Element(element) => {
+            let name = element.name.to_lowercase();
+
+            match name.as_str() {
+                "script" | "style" | "iframe" | "noscript" | "svg" | "form" => {}
+                "br" => out.push_str("<br/>"),
+                "hr" => out.push_str("<hr/>"),
+                "p" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "ul" | "ol" | "li"
+                | "blockquote" | "pre" | "code" | "b" | "strong" | "i" | "em" | "s" | "u" => {
+                    out.push_str(&format!("<{name}>"));
+                    for child in element.children.iter() {
+                        write_xhtml(child, out);
+                    }
+                    out.push_str(&format!("</{name}>"));
+                }
+                "strike" => {
+                    out.push_str("<s>");
+                    for child in element.children.iter() {
+                        write_xhtml(child, out);
+                    }
+                    out.push_str("</s>");
+                }
+                "a" => {
+                    let href = element
+                        .attributes
+                        .get("href")
+                        .cloned()
+                        .flatten()
+                        .unwrap_or_default();
+                    out.push_str(&format!("<a href=\"{}\">", escape(&decode_entities(&href))));
+                    for child in element.children.iter() {
+                        write_xhtml(child, out);
+                    }
+                    out.push_str("</a>");
+                }
+                _ => {
+                    for child in element.children.iter() {
+                        write_xhtml(child, out);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find(';').filter(|end| *end < 10) else {
+            decoded.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+
+        let entity = &rest[1..end];
+        let character = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+
+        match character {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+fn file_name(title: &str) -> String {
+    let name: String = title
+        .chars()
+        .map(|c| match c.is_alphanumeric() {
+            true => c,
+            false => '-',
+        })
+        .collect();
+
+    let name = name
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-");
+
+    match name.is_empty() {
+        true => "crabfeed-export".to_string(),
+        false => name,
+    }
+}
@@ -1,9 +1,13 @@
 pub mod app;
+pub mod cli;
 pub mod config;
 pub mod data;
 pub mod error;
 pub mod event;
+pub mod export;
+pub mod logging;
 pub mod prelude;
+pub mod serve;
 pub mod time;
 pub mod tui;
 pub mod ui;
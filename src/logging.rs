@@ -0,0 +1,175 @@
+// Logs go to `crabfeed.log` in the data directory, next to the database, so they
+// don't write over the TUI. Past MAX_SIZE the file moves to `crabfeed.log.1`, the
+// one before to `crabfeed.log.2` and so on, keeping KEPT_FILES old files. The last
+// lines are also kept in memory for the log viewer.
+
+use std::collections::VecDeque;
+use std::fs::{create_dir_all, remove_file, rename, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use directories::BaseDirs;
+use env_logger::{Target, WriteStyle};
+use log::LevelFilter;
+
+use crate::config::Settings;
+use crate::error::Error;
+use crate::AppResult;
+
+pub const LOG_FILE: &str = "crabfeed.log";
+pub const MAX_SIZE: u64 = 1024 * 1024;
+pub const KEPT_FILES: usize = 3;
+const RECENT_LINES: usize = 1000;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+pub fn log_dir() -> PathBuf {
+    let dir = BaseDirs::new().expect("Failed to get base directories");
+    dir.data_local_dir().join("crabfeed")
+}
+
+// The level passed on the command line wins over the one in the settings
+pub fn log_level(cli: Option<LevelFilter>, config: &Settings) -> AppResult<LevelFilter> {
+    if let Some(level) = cli {
+        return Ok(level);
+    }
+
+    match &config.log_level {
+        Some(level) => parse_level(level),
+        None => Ok(LevelFilter::Info),
+    }
+}
+
+pub fn parse_level(level: &str) -> AppResult<LevelFilter> {
+    LevelFilter::from_str(level).map_err(|_| Error::Generic(format!("Invalid log level {level}")))
+}
+
+pub fn init(level: LevelFilter) -> AppResult<()> {
+    let dir = log_dir();
+    create_dir_all(&dir)?;
+    let file = RotatingFile::open(dir.join(LOG_FILE), MAX_SIZE, KEPT_FILES)?;
+
+    env_logger::builder()
+        .target(Target::Pipe(Box::new(file)))
+        .write_style(WriteStyle::Never)
+        .filter_level(level)
+        .init();
+
+    Ok(())
+}
+
+// The latest log lines of this run, oldest first
+pub fn recent_lines() -> Vec<String> {
+    match RECENT.lock() {
+        Ok(recent) => recent.iter().cloned().collect(),
+        Err(_) => vec![],
+    }
+}
+
+fn remember(lines: &[u8]) {
+    let Ok(mut recent) = RECENT.lock() else {
+        return;
+    };
+
+    for line in String::from_utf8_lossy(lines).lines() {
+        if line.is_empty() {
+            continue;
+        }
+        recent.push_back(line.to_string());
+        if recent.len() > RECENT_LINES {
+            recent.pop_front();
+        }
+    }
+}
+
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    kept: usize,
+    // A line may come in several writes, it is only written out once complete
+    pending: Vec<u8>,
+}
+
+impl RotatingFile {
+    pub fn open(path: PathBuf, max_size: u64, kept: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_size,
+            kept,
+            pending: vec![],
+        })
+    }
+
+    fn write_lines(&mut self, lines: &[u8]) -> io::Result<()> {
+        if self.size > 0 && self.size + lines.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        self.file.write_all(lines)?;
+        self.size += lines.len() as u64;
+        remember(lines);
+
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        let oldest = numbered(&self.path, self.kept);
+        if oldest.exists() {
+            remove_file(&oldest)?;
+        }
+        for number in (1..self.kept).rev() {
+            let from = numbered(&self.path, number);
+            if from.exists() {
+                rename(&from, numbered(&self.path, number + 1))?;
+            }
+        }
+
+        match self.kept {
+            0 => remove_file(&self.path)?,
+            _ => rename(&self.path, numbered(&self.path, 1))?,
+        }
+
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+// `crabfeed.log` numbered 2 is `crabfeed.log.2`
+fn numbered(path: &Path, number: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{number}"));
+    PathBuf::from(name)
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+
+        if let Some(end) = self.pending.iter().rposition(|byte| *byte == b'\n') {
+            let lines: Vec<u8> = self.pending.drain(..=end).collect();
+            self.write_lines(&lines)?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            let rest = std::mem::take(&mut self.pending);
+            self.write_lines(&rest)?;
+        }
+        self.file.flush()
+    }
+}
@@ -1,19 +1,58 @@
 use crabfeed::app::App;
+use crabfeed::cli::{parse_args, take_log_level, Command};
 use crabfeed::config::get_configuration;
+use crabfeed::data::data::{export_entries, feed_entry_ids, import_feed};
+use crabfeed::logging::{self, log_level};
+use crabfeed::serve::serve;
 use crabfeed::AppResult;
-use env_logger::Target;
-use log::LevelFilter;
+use tokio::io::AsyncReadExt;
 
 #[tokio::main]
 async fn main() -> AppResult<()> {
+    let (cli_level, args) = take_log_level(std::env::args().skip(1))?;
+    let command = parse_args(args)?;
     let config = get_configuration()?;
 
-    env_logger::builder()
-        .target(Target::Stdout)
-        .filter_level(LevelFilter::Info)
-        .init();
+    logging::init(log_level(cli_level, &config)?)?;
 
-    App::new(config).run()?;
+    match command {
+        Command::Tui => {
+            App::new(config).run()?;
+        }
+        Command::Export(args) => {
+            let mut entry_ids = args.entries;
+            let mut title = "Crabfeed Export".to_string();
+
+            if let Some(feed) = args.feed {
+                let (feed_title, feed_entries) =
+                    feed_entry_ids(config.database_url.clone(), &feed).await?;
+                title = feed_title;
+                entry_ids.extend(feed_entries);
+            }
+
+            let path = export_entries(
+                config.database_url,
+                &entry_ids,
+                args.format,
+                &title,
+                args.output,
+            )
+            .await?;
+
+            println!("Exported to {}", path.display());
+        }
+        Command::Serve(address) => {
+            let listener = tokio::net::TcpListener::bind(address).await?;
+            serve(config, listener).await?;
+        }
+        Command::Import => {
+            let mut document = vec![];
+            tokio::io::stdin().read_to_end(&mut document).await?;
+
+            let title = import_feed(&config, document).await?;
+            println!("Imported {title}");
+        }
+    }
 
     Ok(())
 }
@@ -12,31 +12,97 @@ pub struct Feed {
     pub description: Option<String>,
     pub language: Option<String>,
     pub published: Option<NaiveDateTime>,
+    pub url: Option<String>,
+    pub custom_title: Option<String>,
+    pub notes: Option<String>,
+    pub redirected_from: Option<String>,
+    pub error: Option<String>,
+    pub folder: Option<String>,
+}
+
+impl Feed {
+    // A title set by the user wins over the one the feed reports
+    pub fn display_title(&self) -> Option<String> {
+        self.custom_title.clone().or(self.title.clone())
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct FeedData {
     pub id: i64,
     pub title: String,
+    pub remote_title: String,
     pub url: String,
     pub description: String,
+    pub notes: String,
+    pub redirected_from: Option<String>,
+    pub error: Option<String>,
+    pub folder: Option<String>,
+    pub query: Option<String>,
+    pub unread: usize,
 }
 
 impl From<Feed> for FeedData {
     fn from(feed: Feed) -> Self {
         Self {
             id: feed.id,
-            title: feed.title.unwrap_or_default(),
-            url: String::new(),
+            title: feed.display_title().unwrap_or_default(),
+            remote_title: feed.title.unwrap_or_default(),
+            url: feed.url.unwrap_or_default(),
             description: feed.description.unwrap_or_default(),
+            notes: feed.notes.unwrap_or_default(),
+            redirected_from: feed.redirected_from,
+            error: feed.error,
+            folder: feed.folder,
+            query: None,
+            unread: 0,
         }
     }
 }
 
+// A local entry and the id the sync server knows it by
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RemoteId {
+    pub local_id: i64,
+    pub remote_id: String,
+}
+
+// A feed on the sync server, `created` when the sync added it rather than finding it
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SyncFeed {
+    pub local_id: i64,
+    pub remote_id: String,
+    pub created: bool,
+}
+
+// A read or star change waiting in the sync queue
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct QueuedChange {
+    pub id: i64,
+    pub action: String,
+    pub remote_id: String,
+}
+
+// The user editable properties of a feed
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FeedProperties {
+    pub title: Option<String>,
+    pub url: String,
+    pub notes: Option<String>,
+}
+
 impl FeedData {
     pub fn update_url(&mut self, url: String) {
         self.url = url;
     }
+
+    pub fn update_unread(&mut self, unread: usize) {
+        self.unread = unread;
+    }
+
+    pub fn is_smart_folder(&self) -> bool {
+        self.query.is_some()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -119,7 +185,7 @@ impl FeedBuilder {
     }
 }
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq, sqlx::FromRow)]
 pub struct Entry {
     pub id: i64,
     pub feed_id: i64,
@@ -130,6 +196,11 @@ pub struct Entry {
     pub summary: Option<String>,
     pub source: Option<String>,
     pub read: Option<bool>,
+    pub starred: Option<bool>,
+    pub hidden: Option<bool>,
+    pub guid: Option<String>,
+    pub canonical_link: Option<String>,
+    pub line_index: Option<i64>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -141,6 +212,12 @@ pub struct EntryData {
     pub links: Vec<Link>,
     pub media: Option<Media>,
     pub read: bool,
+    pub starred: bool,
+    pub tags: Vec<String>,
+    // Copies of the entry published by other feeds
+    pub duplicates: Vec<i64>,
+    // Where reading stopped, the first line shown
+    pub line_index: u16,
 }
 
 impl From<Entry> for EntryData {
@@ -153,6 +230,13 @@ impl From<Entry> for EntryData {
             links: vec![],
             media: None,
             read: value.read.unwrap_or_default(),
+            starred: value.starred.unwrap_or_default(),
+            tags: vec![],
+            duplicates: vec![],
+            line_index: value
+                .line_index
+                .unwrap_or_default()
+                .clamp(0, u16::MAX as i64) as u16,
         }
     }
 }
@@ -169,6 +253,14 @@ impl EntryData {
     pub fn update_description(&mut self, description: String) {
         self.description = description;
     }
+
+    pub fn update_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+    }
+
+    pub fn update_line_index(&mut self, line_index: u16) {
+        self.line_index = line_index;
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -180,6 +272,16 @@ pub struct NewEntry {
     pub media_id: Option<i64>,
     pub summary: Option<String>,
     pub source: Option<String>,
+    pub guid: Option<String>,
+    pub canonical_link: Option<String>,
+}
+
+// An entry added by an update, with the hook commands its matching rules asked for
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InsertedEntry {
+    pub id: i64,
+    pub feed_id: i64,
+    pub hooks: Vec<String>,
 }
 
 #[derive(Default)]
@@ -191,6 +293,8 @@ pub struct EntryBuilder {
     media_id: Option<i64>,
     summary: Option<String>,
     source: Option<String>,
+    guid: Option<String>,
+    canonical_link: Option<String>,
 }
 
 impl EntryBuilder {
@@ -263,6 +367,16 @@ impl EntryBuilder {
         self
     }
 
+    pub fn guid(&mut self, guid: Option<String>) -> &mut Self {
+        self.guid = guid;
+        self
+    }
+
+    pub fn canonical_link(&mut self, canonical_link: Option<String>) -> &mut Self {
+        self.canonical_link = canonical_link;
+        self
+    }
+
     pub fn build(&self) -> AppResult<NewEntry> {
         Ok(NewEntry {
             feed_id: self.feed_id,
@@ -272,6 +386,8 @@ impl EntryBuilder {
             media_id: self.media_id,
             summary: self.summary.clone(),
             source: self.source.clone(),
+            guid: self.guid.clone(),
+            canonical_link: self.canonical_link.clone(),
         })
     }
 }
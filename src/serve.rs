@@ -0,0 +1,327 @@
+// `crabfeed serve` publishes the database as Atom feeds over HTTP, planet style:
+//   /                       an index of the feeds below
+//   /all.atom               every subscription
+//   /starred.atom           starred entries
+//   /folder/<name>.atom     a smart folder, its name lower case with dashes
+// Each feed holds the newest ENTRY_LIMIT entries and is built per request.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, SecondsFormat, Utc};
+use log::{debug, info};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::config::{Settings, SmartFolder};
+use crate::data::data::query_export_entries;
+use crate::data::query::EntryQuery;
+use crate::export::{escape, ExportEntry};
+use crate::AppResult;
+
+pub const ENTRY_LIMIT: usize = 100;
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+// Seconds a client gets to send its request headers
+const REQUEST_TIMEOUT: u64 = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Planet {
+    All,
+    Starred,
+    Folder(SmartFolder),
+}
+
+impl Planet {
+    pub fn title(&self) -> String {
+        match self {
+            Planet::All => "All Subscriptions".to_string(),
+            Planet::Starred => "Starred".to_string(),
+            Planet::Folder(folder) => folder.name.clone(),
+        }
+    }
+
+    pub fn path(&self) -> String {
+        match self {
+            Planet::All => "/all.atom".to_string(),
+            Planet::Starred => "/starred.atom".to_string(),
+            Planet::Folder(folder) => format!("/folder/{}.atom", slug(&folder.name)),
+        }
+    }
+
+    fn query(&self) -> AppResult<EntryQuery> {
+        match self {
+            Planet::All => "".parse(),
+            Planet::Starred => "starred".parse(),
+            Planet::Folder(folder) => folder.query.parse(),
+        }
+    }
+}
+
+pub fn planets(config: &Settings) -> Vec<Planet> {
+    let mut planets = vec![Planet::All, Planet::Starred];
+    planets.extend(config.smart_folders.iter().cloned().map(Planet::Folder));
+    planets
+}
+
+pub fn route(config: &Settings, path: &str) -> Option<Planet> {
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    planets(config)
+        .into_iter()
+        .find(|planet| planet.path() == path)
+}
+
+// Folder names as they appear in urls, e.g. "Rust & Friends" becomes "rust-friends"
+pub fn slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+}
+
+pub fn render_atom(planet: &Planet, self_url: &str, entries: &[(i64, ExportEntry)]) -> String {
+    let updated = entries
+        .iter()
+        .filter_map(|(_, entry)| entry.date)
+        .max()
+        .unwrap_or_else(|| Utc::now().naive_utc());
+
+    let mut atom = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+        <feed xmlns=\"http://www.w3.org/2005/Atom\">\n\
+        <title>{}</title>\n\
+        <id>urn:crabfeed:feed{}</id>\n\
+        <link rel=\"self\" href=\"{}\"/>\n\
+        <updated>{}</updated>\n\
+        <generator>crabfeed</generator>\n",
+        escape(&planet.title()),
+        escape(&planet.path()),
+        escape(self_url),
+        atom_date(updated)
+    );
+
+    for (id, entry) in entries {
+        // Entries keep their own link as id so readers can match them across planets
+        let entry_id = entry
+            .link
+            .clone()
+            .unwrap_or_else(|| format!("urn:crabfeed:entry:{id}"));
+
+        atom.push_str("<entry>\n");
+        atom.push_str(&format!("<title>{}</title>\n", escape(&entry.title)));
+        atom.push_str(&format!("<id>{}</id>\n", escape(&entry_id)));
+        if let Some(link) = &entry.link {
+            atom.push_str(&format!(
+                "<link rel=\"alternate\" href=\"{}\"/>\n",
+                escape(link)
+            ));
+        }
+        atom.push_str(&format!(
+            "<updated>{}</updated>\n",
+            atom_date(entry.date.unwrap_or(updated))
+        ));
+        atom.push_str(&format!(
+            "<author><name>{}</name></author>\n",
+            escape(entry.author.as_ref().unwrap_or(&entry.feed))
+        ));
+        atom.push_str(&format!(
+            "<source><title>{}</title></source>\n",
+            escape(&entry.feed)
+        ));
+        atom.push_str(&format!(
+            "<content type=\"html\">{}</content>\n",
+            escape(&entry.content)
+        ));
+        atom.push_str("</entry>\n");
+    }
+
+    atom.push_str("</feed>\n");
+    atom
+}
+
+fn atom_date(date: NaiveDateTime) -> String {
+    date.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn render_index(config: &Settings) -> String {
+    let planets = planets(config);
+
+    let links = planets
+        .iter()
+        .map(|planet| {
+            format!(
+                "<link rel=\"alternate\" type=\"application/atom+xml\" title=\"{}\" href=\"{}\"/>",
+                escape(&planet.title()),
+                planet.path()
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let items = planets
+        .iter()
+        .map(|planet| {
+            format!(
+                "<li><a href=\"{}\">{}</a></li>",
+                planet.path(),
+                escape(&planet.title())
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    format!(
+        "<!DOCTYPE html>\n\
+        <html>\n\
+        <head>\n\
+        <meta charset=\"utf-8\"/>\n\
+        <title>crabfeed</title>\n\
+        {links}\n\
+        </head>\n\
+        <body>\n<ul>\n{items}\n</ul>\n</body>\n\
+        </html>\n"
+    )
+}
+
+pub async fn serve(config: Settings, listener: TcpListener) -> AppResult<()> {
+    info!("Serving feeds on http://{}/", listener.local_addr()?);
+    let config = Arc::new(config);
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let config = config.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &config).await {
+                debug!("Request from {peer} failed: {e}");
+            }
+        });
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, config: &Settings) -> AppResult<()> {
+    // A client that never finishes its headers would otherwise hold its task forever
+    let request = match tokio::time::timeout(
+        Duration::from_secs(REQUEST_TIMEOUT),
+        read_request(&mut stream),
+    )
+    .await
+    {
+        Ok(request) => request?,
+        Err(_) => {
+            return respond(
+                &mut stream,
+                "408 Request Timeout",
+                "text/plain",
+                "Request timed out",
+            )
+            .await
+        }
+    };
+
+    let Some(request) = request else {
+        return respond(&mut stream, "400 Bad Request", "text/plain", "Bad request").await;
+    };
+
+    let request = String::from_utf8_lossy(&request);
+    let mut parts = request.split_whitespace();
+    let (method, path) = (
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or("/"),
+    );
+
+    if method != "GET" {
+        return respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            "Only GET is supported",
+        )
+        .await;
+    }
+
+    if path == "/" {
+        let index = render_index(config);
+        return respond(&mut stream, "200 OK", "text/html; charset=utf-8", &index).await;
+    }
+
+    let Some(planet) = route(config, path) else {
+        return respond(&mut stream, "404 Not Found", "text/plain", "Not found").await;
+    };
+
+    let host = request
+        .lines()
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("host").then(|| value.trim())
+        })
+        .unwrap_or("localhost");
+    let self_url = format!("http://{host}{}", planet.path());
+
+    let entries = match planet.query() {
+        Ok(entry_query) => {
+            query_export_entries(config.database_url.clone(), &entry_query, ENTRY_LIMIT).await
+        }
+        Err(e) => Err(e),
+    };
+
+    match entries {
+        Ok(entries) => {
+            let atom = render_atom(&planet, &self_url, &entries);
+            respond(
+                &mut stream,
+                "200 OK",
+                "application/atom+xml; charset=utf-8",
+                &atom,
+            )
+            .await
+        }
+        Err(e) => {
+            debug!("Could not build {}: {e}", planet.path());
+            respond(
+                &mut stream,
+                "500 Internal Server Error",
+                "text/plain",
+                "Could not read the database",
+            )
+            .await
+        }
+    }
+}
+
+// Reads up to the end of the headers, None when the client stops early or sends too much
+async fn read_request(stream: &mut TcpStream) -> AppResult<Option<Vec<u8>>> {
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 || request.len() + read > MAX_REQUEST_SIZE {
+            return Ok(None);
+        }
+        request.extend_from_slice(&buf[..read]);
+    }
+
+    Ok(Some(request))
+}
+
+async fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> AppResult<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\n\
+        Content-Type: {content_type}\r\n\
+        Content-Length: {}\r\n\
+        Connection: close\r\n\r\n\
+        {body}",
+        body.len()
+    );
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use crate::{config::Settings, data::data::DataEvent};
 
 use super::{
-    components::{BlockText, Popup},
+    components::{centered_rect, BlockText, Popup},
     UiCallback, View,
 };
 
@@ -12,6 +12,9 @@ use ratatui::{
     widgets::{Paragraph, Wrap},
 };
 
+const WIDTH: u16 = 60;
+const HEIGHT: u16 = 3;
+
 pub struct Add {
     pub input: Vec<char>,
     pub input_cursor_position: usize,
@@ -51,11 +54,15 @@ impl View for Add {
                     .wrap(Wrap::default()),
                 ),
         ))
-        .height(3)
-        .width(60)
+        .height(HEIGHT)
+        .width(WIDTH)
         .render(area, buf);
     }
 
+    fn bounds(&self, area: Rect) -> Rect {
+        centered_rect(area, WIDTH, HEIGHT)
+    }
+
     fn handle_key_event(&mut self, key: KeyEvent) -> Option<UiCallback> {
         match key.code {
             _ if key.code == KeyCode::Char('q') || key.code == KeyCode::Esc => {
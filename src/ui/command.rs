@@ -0,0 +1,358 @@
+// The `:` command line. Commands are typed into the status bar and run on Enter:
+//   :add <url>                    subscribe to a feed
+//   :update                       fetch all feeds, syncing with the server if one is set up
+//   :mark-all-read [feed]         mark the current or named feed read
+//   :export [markdown|html|epub]  export the marked entries or the current feed
+//   :set [no]unread-only[!]       hide read entries, `!` toggles
+//   :open <n>                     open the n-th entry, or the n-th link of an entry
+//   :feed <title>                 jump to a feed
+//   :stats                        show reading statistics
+//   :messages                     show the message history
+//   :logs                         show the log of this run
+//   :quit
+// Tab completes command names and feed titles, Up and Down walk the history.
+
+use std::str::FromStr;
+
+use crate::app::Severity;
+use crate::error::Error;
+use crate::export::ExportFormat;
+use crate::{data::data::DataEvent, AppResult};
+
+use super::components::BlockLabel;
+use super::UiCallback;
+
+use clipboard::{ClipboardContext, ClipboardProvider};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+
+pub const COMMANDS: [&str; 11] = [
+    "add",
+    "export",
+    "feed",
+    "logs",
+    "mark-all-read",
+    "messages",
+    "open",
+    "quit",
+    "set",
+    "stats",
+    "update",
+];
+
+const OPTIONS: [&str; 3] = ["unread-only", "nounread-only", "unread-only!"];
+
+const HISTORY_LEN: usize = 100;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Add(String),
+    Update,
+    MarkAllRead(Option<String>),
+    Export(Option<ExportFormat>),
+    // `None` toggles the option
+    Set(UiOption, Option<bool>),
+    Open(usize),
+    Feed(String),
+    Stats,
+    Messages,
+    Logs,
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UiOption {
+    UnreadOnly,
+}
+
+impl FromStr for Command {
+    type Err = Error;
+
+    fn from_str(line: &str) -> AppResult<Self> {
+        let line = line.trim().trim_start_matches(':');
+        let (name, argument) = match line.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, Some(argument.trim()).filter(|arg| !arg.is_empty())),
+            None => (line, None),
+        };
+
+        let missing = || Error::Generic(format!("Missing argument for :{name}"));
+
+        match name {
+            "add" => Ok(Command::Add(argument.ok_or_else(missing)?.to_string())),
+            "update" => Ok(Command::Update),
+            "mark-all-read" => Ok(Command::MarkAllRead(argument.map(str::to_string))),
+            "export" => Ok(Command::Export(argument.map(str::parse).transpose()?)),
+            "set" => parse_option(argument.ok_or_else(missing)?),
+            "open" => {
+                let argument = argument.ok_or_else(missing)?;
+                match argument.parse() {
+                    Ok(number) if number > 0 => Ok(Command::Open(number)),
+                    _ => Err(Error::Generic(format!("Invalid number {argument}"))),
+                }
+            }
+            "feed" => Ok(Command::Feed(argument.ok_or_else(missing)?.to_string())),
+            "stats" => Ok(Command::Stats),
+            "messages" => Ok(Command::Messages),
+            "logs" => Ok(Command::Logs),
+            "q" | "quit" => Ok(Command::Quit),
+            _ => Err(Error::Generic(format!("Unknown command :{name}"))),
+        }
+    }
+}
+
+fn parse_option(option: &str) -> AppResult<Command> {
+    let (name, value) = match option.strip_suffix('!') {
+        Some(name) => (name, None),
+        None => match option.strip_prefix("no") {
+            Some(name) => (name, Some(false)),
+            None => (option, Some(true)),
+        },
+    };
+
+    match name {
+        "unread-only" => Ok(Command::Set(UiOption::UnreadOnly, value)),
+        _ => Err(Error::Generic(format!("Unknown option {option}"))),
+    }
+}
+
+impl Command {
+    pub fn run(self) -> UiCallback {
+        Box::new(move |app| {
+            match &self {
+                Command::Add(url) => {
+                    app.dispatch(DataEvent::AddFeed(url.clone()))?;
+                    app.dispatch(DataEvent::Refresh)?;
+                }
+                Command::Update => app.dispatch(DataEvent::UpdateFeeds)?,
+                Command::MarkAllRead(feed) => match app.ui.mark_all_read(feed.as_deref()) {
+                    Some(entry_ids) if !entry_ids.is_empty() => {
+                        app.dispatch(DataEvent::ReadEntries(entry_ids))?
+                    }
+                    Some(_) => {}
+                    None => app.ui.notify(Severity::Error, "No such feed".to_string()),
+                },
+                Command::Export(format) => {
+                    if let Some(callback) = app.ui.export_current(*format) {
+                        callback(app)?;
+                    }
+                }
+                Command::Set(UiOption::UnreadOnly, value) => app.ui.set_unread_only(*value),
+                Command::Open(number) => match app.ui.open_number(*number) {
+                    Some(callback) => callback(app)?,
+                    None => app
+                        .ui
+                        .notify(Severity::Error, format!("Nothing to open at {number}")),
+                },
+                Command::Feed(title) => {
+                    if !app.ui.go_to_feed(title) {
+                        app.ui
+                            .notify(Severity::Error, format!("No feed named {title}"));
+                    }
+                }
+                Command::Stats => {
+                    app.ui.open_statistics();
+                    app.dispatch(DataEvent::Statistics)?;
+                }
+                Command::Messages => app.ui.open_messages(),
+                Command::Logs => app.ui.open_logs(),
+                Command::Quit => app.is_running = false,
+            }
+            Ok(())
+        })
+    }
+}
+
+pub struct CommandLine {
+    pub active: bool,
+    input: Vec<char>,
+    input_i: usize,
+    history: Vec<String>,
+    history_i: Option<usize>,
+    completions: Vec<String>,
+    completion_i: usize,
+}
+
+impl Default for CommandLine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandLine {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            input: Vec::new(),
+            input_i: 0,
+            history: vec![],
+            history_i: None,
+            completions: vec![],
+            completion_i: 0,
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.active = true;
+        self.set_input(String::new());
+        self.history_i = None;
+    }
+
+    fn close(&mut self) {
+        self.active = false;
+        self.completions.clear();
+    }
+
+    fn set_input(&mut self, input: String) {
+        self.input = input.chars().collect();
+        self.input_i = self.input.len();
+    }
+
+    fn text(&self) -> String {
+        self.input.iter().collect()
+    }
+
+    // Repeated tabs cycle through the candidates found by the first one
+    fn complete(&mut self, feed_titles: &[String]) {
+        if self.completions.is_empty() {
+            self.completions = completions(&self.text(), feed_titles);
+            self.completion_i = 0;
+        } else {
+            self.completion_i = (self.completion_i + 1) % self.completions.len();
+        }
+
+        if let Some(completion) = self.completions.get(self.completion_i).cloned() {
+            self.set_input(completion);
+        }
+    }
+
+    fn recall(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+
+        let index = match (self.history_i, older) {
+            (None, true) => Some(self.history.len() - 1),
+            (None, false) => None,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index + 1 < self.history.len() => Some(index + 1),
+            (Some(_), false) => None,
+        };
+
+        self.history_i = index;
+        match index {
+            Some(index) => self.set_input(self.history[index].clone()),
+            None => self.set_input(String::new()),
+        }
+    }
+
+    fn submit(&mut self) -> Option<UiCallback> {
+        let line = self.text().trim().to_string();
+        self.close();
+
+        if line.is_empty() {
+            return None;
+        }
+
+        self.history.retain(|entry| *entry != line);
+        self.history.push(line.clone());
+        if self.history.len() > HISTORY_LEN {
+            self.history.remove(0);
+        }
+
+        match line.parse::<Command>() {
+            Ok(command) => Some(command.run()),
+            Err(e) => Some(Box::new(move |app| {
+                app.ui.notify(Severity::Error, e.to_string());
+                Ok(())
+            })),
+        }
+    }
+
+    pub fn handle_key_event(
+        &mut self,
+        key: KeyEvent,
+        feed_titles: &[String],
+    ) -> Option<UiCallback> {
+        if key.code != KeyCode::Tab {
+            self.completions.clear();
+        }
+
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Tab => self.complete(feed_titles),
+            KeyCode::Up => self.recall(true),
+            KeyCode::Down => self.recall(false),
+            KeyCode::Left => self.input_i = self.input_i.saturating_sub(1),
+            KeyCode::Right => self.input_i = (self.input_i + 1).min(self.input.len()),
+            KeyCode::Home => self.input_i = 0,
+            KeyCode::End => self.input_i = self.input.len(),
+            _ if key.code == KeyCode::Char('v') && key.modifiers == KeyModifiers::CONTROL => {
+                // Without a clipboard there is nothing to paste
+                let clipboard: Result<ClipboardContext, _> = ClipboardProvider::new();
+                if let Ok(contents) = clipboard.and_then(|mut clipboard| clipboard.get_contents()) {
+                    for c in contents.chars().filter(|c| *c != '\n') {
+                        self.input.insert(self.input_i, c);
+                        self.input_i += 1;
+                    }
+                }
+            }
+            KeyCode::Char(c) => {
+                self.input.insert(self.input_i, c);
+                self.input_i += 1;
+            }
+            KeyCode::Backspace => {
+                // Backspace on an empty line leaves command mode like in vim
+                if self.input.is_empty() {
+                    self.close();
+                } else if self.input_i > 0 {
+                    self.input.remove(self.input_i - 1);
+                    self.input_i -= 1;
+                }
+            }
+            KeyCode::Delete if self.input_i < self.input.len() => {
+                self.input.remove(self.input_i);
+            }
+            _ => {}
+        }
+
+        None
+    }
+}
+
+impl Widget for &CommandLine {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut line: String = self.input[..self.input_i].iter().collect();
+        line.push('█');
+        line.extend(self.input[self.input_i..].iter());
+
+        BlockLabel::new()
+            .label(format!(":{line}"))
+            .render(area, buf);
+    }
+}
+
+// Candidates for the word under completion, either a command name or its argument
+pub fn completions(input: &str, feed_titles: &[String]) -> Vec<String> {
+    let Some((name, argument)) = input.split_once(' ') else {
+        return COMMANDS
+            .iter()
+            .filter(|command| command.starts_with(input))
+            .map(|command| format!("{command} "))
+            .collect();
+    };
+
+    let argument = argument.to_lowercase();
+    let candidates: Vec<String> = match name {
+        "feed" | "mark-all-read" => feed_titles.to_vec(),
+        "export" => vec!["markdown".into(), "html".into(), "epub".into()],
+        "set" => OPTIONS.iter().map(|option| option.to_string()).collect(),
+        _ => vec![],
+    };
+
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.to_lowercase().starts_with(&argument))
+        .map(|candidate| format!("{name} {candidate}"))
+        .collect()
+}
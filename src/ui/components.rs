@@ -237,12 +237,7 @@ where
     W: WidgetRef,
 {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        let new_area = Rect::new(
-            area.x + area.width / 2 - self.width / 2,
-            area.y + area.height / 2 - self.height / 2,
-            self.width,
-            self.height,
-        );
+        let new_area = centered_rect(area, self.width, self.height);
 
         Clear.render_ref(new_area, buf);
         self.inner_widget.render_ref(new_area, buf);
@@ -257,3 +252,31 @@ where
         self.render_ref(area, buf);
     }
 }
+
+pub fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    Rect::new(
+        area.x + area.width / 2 - width / 2,
+        area.y + area.height / 2 - height / 2,
+        width,
+        height,
+    )
+}
+
+// Maps a terminal row inside a bordered `ItemList` to the index of the item drawn there.
+// Lists are rendered from a fresh copy of their state, so the offset is always the
+// smallest one that keeps the selection in view.
+pub fn list_row_index(state: &ListState, area: Rect, row: u16, len: usize) -> Option<usize> {
+    let inner = area.inner(Margin::new(1, 1));
+
+    if row < inner.y || row >= inner.y + inner.height {
+        return None;
+    }
+
+    let offset = state
+        .selected()
+        .map(|selected| (selected + 1).saturating_sub(inner.height as usize))
+        .unwrap_or(0);
+    let index = offset + (row - inner.y) as usize;
+
+    (index < len).then_some(index)
+}
@@ -0,0 +1,74 @@
+use crate::config::Settings;
+
+use super::{
+    components::{centered_rect, BlockText, Popup},
+    UiCallback, View,
+};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::*,
+    widgets::{Paragraph, Wrap},
+};
+
+const WIDTH: u16 = 60;
+const HEIGHT: u16 = 4;
+
+// Asks before running `action`, which only runs once
+pub struct Confirm {
+    message: String,
+    action: Option<UiCallback>,
+}
+
+impl Confirm {
+    pub fn new(message: String, action: UiCallback) -> Self {
+        Self {
+            message,
+            action: Some(action),
+        }
+    }
+
+    fn confirm(&mut self) -> Option<UiCallback> {
+        let action = self.action.take()?;
+
+        Some(Box::new(move |app| {
+            app.ui.unset_popup();
+            action(app)
+        }))
+    }
+}
+
+impl View for Confirm {
+    fn render(&self, area: Rect, buf: &mut Buffer, _config: &Settings) {
+        Popup::new(Some(
+            BlockText::default()
+                .title(Some("Confirm".to_string()))
+                .paragraph(
+                    Paragraph::new(vec![
+                        Line::from(self.message.clone()).bold(),
+                        Line::from("(y) Yes  (n) No"),
+                    ])
+                    .alignment(Alignment::Center)
+                    .wrap(Wrap::default()),
+                ),
+        ))
+        .height(HEIGHT)
+        .width(WIDTH)
+        .render(area, buf);
+    }
+
+    fn bounds(&self, area: Rect) -> Rect {
+        centered_rect(area, WIDTH, HEIGHT)
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Option<UiCallback> {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => self.confirm(),
+            KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => Some(Box::new(move |app| {
+                app.ui.unset_popup();
+                Ok(())
+            })),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    config::Settings,
+    data::data::DataEvent,
+    prelude::{FeedData, FeedProperties},
+};
+
+use super::{
+    components::{centered_rect, BlockText, Popup},
+    util::parse_hex,
+    UiCallback, View,
+};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::*,
+    widgets::{Paragraph, Wrap},
+};
+
+const WIDTH: u16 = 70;
+const HEIGHT: u16 = 11;
+
+const LABELS: [&str; 3] = ["Name", "URL", "Notes"];
+
+pub struct EditFeed {
+    feed: FeedData,
+    // Name, URL and notes, in the order of LABELS
+    fields: [Vec<char>; 3],
+    field_i: usize,
+    input_i: usize,
+}
+
+impl EditFeed {
+    pub fn new(feed: FeedData) -> Self {
+        // Only a title that differs from the feed's own counts as a custom name
+        let name = match feed.title == feed.remote_title {
+            true => String::new(),
+            false => feed.title.clone(),
+        };
+
+        let fields: [Vec<char>; 3] = [
+            name.chars().collect(),
+            feed.url.chars().collect(),
+            feed.notes.chars().collect(),
+        ];
+        let input_i = fields[0].len();
+
+        Self {
+            feed,
+            fields,
+            field_i: 0,
+            input_i,
+        }
+    }
+
+    // Extra lines point out where a moved feed used to live and why it failed to update
+    fn height(&self) -> u16 {
+        let notices = [
+            self.feed.redirected_from.is_some(),
+            self.feed.error.is_some(),
+        ];
+        HEIGHT + notices.iter().filter(|notice| **notice).count() as u16
+    }
+
+    fn focus(&mut self, field_i: usize) {
+        self.field_i = field_i % self.fields.len();
+        self.input_i = self.fields[self.field_i].len();
+    }
+
+    fn field(&self, index: usize) -> String {
+        self.fields[index]
+            .iter()
+            .collect::<String>()
+            .trim()
+            .to_string()
+    }
+
+    fn save(&self) -> Option<UiCallback> {
+        let url = self.field(1);
+        if url.is_empty() {
+            return None;
+        }
+
+        let feed_id = self.feed.id;
+        let properties = FeedProperties {
+            title: Some(self.field(0)).filter(|title| !title.is_empty()),
+            url,
+            notes: Some(self.field(2)).filter(|notes| !notes.is_empty()),
+        };
+
+        Some(Box::new(move |app| {
+            app.dispatch(DataEvent::EditFeed(feed_id, properties.clone()))?;
+            app.dispatch(DataEvent::Refresh)?;
+            app.ui.unset_popup();
+            Ok(())
+        }))
+    }
+}
+
+impl View for EditFeed {
+    fn render(&self, area: Rect, buf: &mut Buffer, config: &Settings) {
+        let primary = Style::default().fg(parse_hex(&config.colors.primary));
+
+        let mut lines =
+            vec![Line::from(format!("Feed title: {}", self.feed.remote_title)).italic()];
+
+        if let Some(old_url) = &self.feed.redirected_from {
+            lines.push(Line::from(format!("Moved permanently from {old_url}")).italic());
+        }
+        if let Some(error) = &self.feed.error {
+            lines.push(Line::from(format!("Last update failed: {error}")).red());
+        }
+        lines.push(Line::default());
+
+        for (index, label) in LABELS.iter().enumerate() {
+            let text: String = self.fields[index].iter().collect();
+
+            lines.push(match index == self.field_i {
+                true => {
+                    let (before, after) = self.fields[index].split_at(self.input_i);
+                    Line::from(vec![
+                        Span::styled(format!("{label:>5}: "), primary),
+                        Span::raw(before.iter().collect::<String>()),
+                        Span::raw("█"),
+                        Span::raw(after.iter().collect::<String>()),
+                    ])
+                }
+                false => Line::from(format!("{label:>5}: {text}")),
+            });
+        }
+
+        lines.push(Line::default());
+        lines.push(
+            Line::from("(Tab) next field  (Enter) save  (Esc) cancel").alignment(Alignment::Center),
+        );
+
+        Popup::new(Some(
+            BlockText::default()
+                .title(Some("Edit Feed".to_string()))
+                .paragraph(Paragraph::new(lines).wrap(Wrap::default())),
+        ))
+        .height(self.height())
+        .width(WIDTH)
+        .render(area, buf);
+    }
+
+    fn bounds(&self, area: Rect) -> Rect {
+        centered_rect(area, WIDTH, self.height())
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Option<UiCallback> {
+        match key.code {
+            KeyCode::Esc => {
+                return Some(Box::new(move |app| {
+                    app.ui.unset_popup();
+                    Ok(())
+                }))
+            }
+            KeyCode::Enter => return self.save(),
+            KeyCode::Tab | KeyCode::Down => self.focus(self.field_i + 1),
+            KeyCode::BackTab | KeyCode::Up => self.focus(self.field_i + self.fields.len() - 1),
+            KeyCode::Left => self.input_i = self.input_i.saturating_sub(1),
+            KeyCode::Right => {
+                self.input_i = (self.input_i + 1).min(self.fields[self.field_i].len())
+            }
+            KeyCode::Char(c) => {
+                self.fields[self.field_i].insert(self.input_i, c);
+                self.input_i += 1;
+            }
+            KeyCode::Backspace if self.input_i > 0 => {
+                self.fields[self.field_i].remove(self.input_i - 1);
+                self.input_i -= 1;
+            }
+            KeyCode::Delete if self.input_i < self.fields[self.field_i].len() => {
+                self.fields[self.field_i].remove(self.input_i);
+            }
+            _ => {}
+        }
+
+        None
+    }
+}
@@ -2,7 +2,7 @@ use crate::app::{ActiveBlock, Route, RouteId};
 use crate::config::Settings;
 use crate::data::data::DataEvent;
 use crate::prelude::EntryData;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::style::Stylize;
 use ratatui::{buffer::Buffer, layout::Rect, prelude::*, style::Style, widgets::ListState};
 
@@ -14,6 +14,8 @@ pub struct Entries {
     list_state: ListState,
     index: usize,
     entry_items: Vec<Vec<EntryData>>,
+    marked: Vec<i64>,
+    unread_only: bool,
     selected: bool,
 }
 
@@ -23,12 +25,16 @@ impl Entries {
             Some(entry_data) => Self {
                 list_state: ListState::default(),
                 entry_items: entry_data,
+                marked: vec![],
+                unread_only: false,
                 index: 0,
                 selected: false,
             },
             None => Self {
                 list_state: ListState::default(),
                 entry_items: vec![],
+                marked: vec![],
+                unread_only: false,
                 index: 0,
                 selected: false,
             },
@@ -39,6 +45,12 @@ impl Entries {
         self.selected = selected;
     }
 
+    pub fn set_index(&mut self, index: usize) {
+        if index < self.entry_items.len() {
+            self.index = index;
+        }
+    }
+
     pub fn next_index(&mut self) {
         if !self.entry_items.is_empty() {
             if self.index + 1 > self.entry_items.len() - 1 {
@@ -67,25 +79,175 @@ impl Entries {
         self.entry_items = item_groups;
     }
 
-    pub fn remove(&mut self, index: usize) {
+    pub fn update_description(&mut self, entry_id: i64, description: String) {
+        for entry in self.entry_items.iter_mut().flatten() {
+            if entry.id == entry_id {
+                entry.update_description(description.clone());
+            }
+        }
+    }
+
+    pub fn update_position(&mut self, entry_id: i64, line_index: u16) {
+        for entry in self.entry_items.iter_mut().flatten() {
+            if entry.id == entry_id {
+                entry.update_line_index(line_index);
+            }
+        }
+    }
+
+    pub fn entry_ids(&self, index: usize) -> Vec<i64> {
+        self.entry_items
+            .get(index)
+            .map(|items| items.iter().map(|entry| entry.id).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn clear_marked(&mut self) {
+        self.marked.clear();
+    }
+
+    pub fn marked(&self) -> &[i64] {
+        &self.marked
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn unread_only(&self) -> bool {
+        self.unread_only
+    }
+
+    // `None` toggles between all and only unread entries
+    pub fn set_unread_only(&mut self, unread_only: Option<bool>) {
+        self.unread_only = unread_only.unwrap_or(!self.unread_only);
+        self.list_state.select(None);
+    }
+
+    // Marks every entry of a group read and returns the ids that changed
+    pub fn mark_read(&mut self, index: usize) -> Vec<i64> {
+        let Some(items) = self.entry_items.get(index) else {
+            return vec![];
+        };
+
+        let entry_ids: Vec<i64> = items
+            .iter()
+            .filter(|entry| !entry.read)
+            .map(|entry| entry.id)
+            .collect();
+        let copies: Vec<i64> = items
+            .iter()
+            .filter(|entry| !entry.read)
+            .flat_map(|entry| entry.duplicates.iter().copied())
+            .collect();
+
+        for item in self.entry_items.iter_mut().flatten() {
+            if entry_ids.contains(&item.id) || copies.contains(&item.id) {
+                item.read = true;
+            }
+        }
+
+        entry_ids
+    }
+
+    // Opens the n-th listed entry of the current group, counting from 1
+    pub fn open(&mut self, number: usize) -> Option<UiCallback> {
+        if number == 0 || number > self.visible().len() {
+            return None;
+        }
+
+        self.list_state.select(Some(number - 1));
+        self.select_entry()
+    }
+
+    // Opens an entry of the current group by id, showing read entries if it is one
+    pub fn open_id(&mut self, entry_id: i64) -> Option<UiCallback> {
+        let position = self
+            .entry_items
+            .get(self.index)?
+            .iter()
+            .position(|entry| entry.id == entry_id)?;
+
+        if !self.visible().contains(&position) {
+            self.unread_only = false;
+        }
+
+        let row = self.visible().iter().position(|p| *p == position)?;
+        self.list_state.select(Some(row));
+        self.select_entry()
+    }
+
+    // The highlighted entry of the current group
+    pub fn selected_id(&self) -> Option<i64> {
+        let position = self.selected_position()?;
+        self.entry_items
+            .get(self.index)?
+            .get(position)
+            .map(|entry| entry.id)
+    }
+
+    // Highlights an entry of the current group without opening it, if it is listed
+    pub fn select_id(&mut self, entry_id: i64) -> Option<EntryData> {
+        let items = self.entry_items.get(self.index)?;
+        let position = items.iter().position(|entry| entry.id == entry_id)?;
+        let entry = items[position].clone();
+
+        let row = self.visible().iter().position(|p| *p == position)?;
+        self.list_state.select(Some(row));
+        Some(entry)
+    }
+
+    pub fn groups(&self) -> &[Vec<EntryData>] {
+        &self.entry_items
+    }
+
+    // Positions in the current group that are listed
+    fn visible(&self) -> Vec<usize> {
+        self.entry_items
+            .get(self.index)
+            .map(|items| {
+                items
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, entry)| !self.unread_only || !entry.read)
+                    .map(|(position, _)| position)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn selected_position(&self) -> Option<usize> {
+        self.visible().get(self.list_state.selected()?).copied()
+    }
+
+    pub fn remove(&mut self, index: usize) -> Vec<EntryData> {
+        if index >= self.entry_items.len() {
+            return vec![];
+        }
+
         if self.index > 0 {
             self.index -= 1;
         }
-        self.entry_items.remove(index);
+        self.entry_items.remove(index)
+    }
+
+    pub fn insert(&mut self, index: usize, entries: Vec<EntryData>) {
+        let index = index.min(self.entry_items.len());
+        self.entry_items.insert(index, entries);
+        self.index = index;
     }
 
     fn scroll_down(&mut self) -> Option<UiCallback> {
-        if !self.entry_items.is_empty() {
-            if !self.entry_items[self.index].is_empty() {
-                if let Some(index) = self.list_state.selected() {
-                    if index < self.entry_items[self.index].len() - 1 {
-                        self.list_state.select_next();
-                    } else {
-                        self.list_state.select_first();
-                    }
-                } else if self.entry_items[self.index].len() > 0 {
+        let len = self.visible().len();
+        if len > 0 {
+            if let Some(index) = self.list_state.selected() {
+                if index < len - 1 {
+                    self.list_state.select_next();
+                } else {
                     self.list_state.select_first();
                 }
+            } else {
+                self.list_state.select_first();
             }
         }
 
@@ -93,47 +255,91 @@ impl Entries {
     }
 
     fn scroll_up(&mut self) -> Option<UiCallback> {
-        if !self.entry_items.is_empty() {
-            if !self.entry_items[self.index].is_empty() {
-                if let Some(index) = self.list_state.selected() {
-                    if index > 0 {
-                        self.list_state.select(Some(index - 1));
-                    } else {
-                        self.list_state
-                            .select(Some(self.entry_items[self.index].len() - 1));
-                    }
-                } else if self.entry_items[self.index].len() > 0 {
-                    self.list_state
-                        .select(Some(self.entry_items[self.index].len() - 1));
+        let len = self.visible().len();
+        if len > 0 {
+            if let Some(index) = self.list_state.selected() {
+                if index > 0 {
+                    self.list_state.select(Some(index - 1));
+                } else {
+                    self.list_state.select(Some(len - 1));
                 }
+            } else {
+                self.list_state.select(Some(len - 1));
             }
         }
 
         None
     }
 
-    fn select_entry(&mut self) -> Option<UiCallback> {
-        if !self.entry_items.is_empty() {
-            if !self.entry_items[self.index].is_empty() {
-                let entry = Some(
-                    self.entry_items[self.index][self.list_state.selected().unwrap_or(0)].clone(),
-                );
-
-                self.entry_items[self.index][self.list_state.selected().unwrap()].read = true;
-                let entry_id = self.entry_items[self.index][self.list_state.selected().unwrap()].id;
+    fn mark_entry(&mut self) -> Option<UiCallback> {
+        let index = self.selected_position()?;
+        let entry_id = self.entry_items.get(self.index)?.get(index)?.id;
 
-                return Some(Box::new(move |app| {
-                    app.dispatch(DataEvent::ReadEntry(entry_id))?;
-                    app.ui
-                        .set_current_route(Route::new(RouteId::Entry, ActiveBlock::Entry));
-                    app.ui.set_entry(entry.clone());
-                    Ok(())
-                }));
+        match self.marked.iter().position(|id| *id == entry_id) {
+            Some(position) => {
+                self.marked.remove(position);
             }
+            None => self.marked.push(entry_id),
         }
 
         None
     }
+
+    // Exports the marked entries, or the highlighted one if nothing is marked
+    fn export_entries(&mut self) -> Option<UiCallback> {
+        let (entry_ids, title) = if self.marked.is_empty() {
+            let index = self.selected_position()?;
+            let entry = self.entry_items.get(self.index)?.get(index)?;
+            (vec![entry.id], entry.title.clone())
+        } else {
+            (self.marked.clone(), "Selected Entries".to_string())
+        };
+
+        Some(Box::new(move |app| {
+            app.ui.export_entries(entry_ids.clone(), title.clone());
+            Ok(())
+        }))
+    }
+
+    fn star_entry(&mut self) -> Option<UiCallback> {
+        let index = self.selected_position()?;
+
+        let entry = self
+            .entry_items
+            .get_mut(self.index)
+            .and_then(|items| items.get_mut(index))?;
+
+        entry.starred = !entry.starred;
+        let entry_id = entry.id;
+        let starred = entry.starred;
+
+        Some(Box::new(move |app| {
+            app.dispatch(DataEvent::StarEntry(entry_id, starred))?;
+            Ok(())
+        }))
+    }
+
+    fn select_entry(&mut self) -> Option<UiCallback> {
+        let position = self.selected_position()?;
+        let entry = self.entry_items.get(self.index)?.get(position)?.clone();
+        let entry_id = entry.id;
+
+        // The same entry can be listed under its feed and any smart folders, and
+        // other feeds may carry copies of it
+        for item in self.entry_items.iter_mut().flatten() {
+            if item.id == entry_id || entry.duplicates.contains(&item.id) {
+                item.read = true;
+            }
+        }
+
+        Some(Box::new(move |app| {
+            app.dispatch(DataEvent::ReadEntry(entry_id))?;
+            app.ui
+                .set_current_route(Route::new(RouteId::Entry, ActiveBlock::Entry));
+            app.ui.set_entry(Some(entry.clone()));
+            Ok(())
+        }))
+    }
 }
 
 impl View for Entries {
@@ -157,9 +363,20 @@ impl View for Entries {
 
         let items = &self.entry_items[self.index];
 
-        let entries: Vec<(bool, String)> = items
-            .iter()
-            .map(|entry| (entry.read.clone(), entry.title.clone()))
+        let entries: Vec<(bool, String)> = self
+            .visible()
+            .into_iter()
+            .map(|position| &items[position])
+            .map(|entry| {
+                let mut title = match entry.starred {
+                    true => format!("★ {}", entry.title),
+                    false => entry.title.clone(),
+                };
+                if self.marked.contains(&entry.id) {
+                    title = format!("✓ {}", title);
+                }
+                (entry.read, title)
+            })
             .collect();
 
         let list_len = entries.len();
@@ -198,8 +415,13 @@ impl View for Entries {
             }
         }
 
+        let title = match self.unread_only {
+            true => format!("Entries ({} unread)", unread_len),
+            false => format!("Entries ({}/{})", unread_len, list_len),
+        };
+
         ItemList::new(&lines)
-            .title(Some(format!("Entries ({}/{})", unread_len, list_len)))
+            .title(Some(title))
             .style(match self.selected {
                 true => selected_style,
                 false => unselected_style,
@@ -212,6 +434,9 @@ impl View for Entries {
             KeyCode::Char('j') | KeyCode::Down => self.scroll_down(),
             KeyCode::Char('k') | KeyCode::Up => self.scroll_up(),
             KeyCode::Char('l') | KeyCode::Left | KeyCode::Enter => self.select_entry(),
+            KeyCode::Char('s') => self.star_entry(),
+            KeyCode::Char(' ') => self.mark_entry(),
+            KeyCode::Char('e') => self.export_entries(),
             KeyCode::Char('h') | KeyCode::Right => {
                 return Some(Box::new(move |app| {
                     app.ui.back();
@@ -221,4 +446,27 @@ impl View for Entries {
             _ => None,
         }
     }
+
+    fn handle_mouse_event(
+        &mut self,
+        event: MouseEvent,
+        area: Rect,
+        double_click: bool,
+    ) -> Option<UiCallback> {
+        match event.kind {
+            MouseEventKind::ScrollDown => self.scroll_down(),
+            MouseEventKind::ScrollUp => self.scroll_up(),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let len = self.visible().len();
+                let index = list_row_index(&self.list_state, area, event.row, len)?;
+                self.list_state.select(Some(index));
+
+                if double_click {
+                    return self.select_entry();
+                }
+                None
+            }
+            _ => None,
+        }
+    }
 }
@@ -1,12 +1,15 @@
 use super::components::*;
-use super::util::{parse_hex, parse_html};
+use super::util::{parse_hex, parse_html, reading_progress};
 use super::{UiCallback, View};
 use crate::config::Settings;
+use crate::data::data::DataEvent;
 use crate::prelude::EntryData;
 use clipboard::{ClipboardContext, ClipboardProvider};
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Position;
 use ratatui::prelude::*;
 use ratatui::widgets::{ListState, Paragraph, Wrap};
+use std::rc::Rc;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 enum Section {
@@ -37,11 +40,105 @@ impl Entry {
 
     pub fn set_entry(&mut self, entry: EntryData) {
         self.entry = Some(entry.clone());
-        self.line_index = 0;
+        self.line_index = entry.line_index;
         if let Ok(description) = parse_html(entry.description) {
             self.description = Some(description);
         }
     }
+
+    pub fn line_index(&self) -> u16 {
+        self.line_index
+    }
+
+    pub fn set_line_index(&mut self, line_index: u16) {
+        self.line_index = line_index;
+    }
+
+    // The entry and where reading stopped, if it moved since the entry was opened
+    pub fn unsaved_position(&self) -> Option<(i64, u16)> {
+        let entry = self.entry.as_ref()?;
+        (entry.line_index != self.line_index).then_some((entry.id, self.line_index))
+    }
+
+    pub fn update_position(&mut self, entry_id: i64, line_index: u16) {
+        if let Some(entry) = self.entry.as_mut().filter(|entry| entry.id == entry_id) {
+            entry.update_line_index(line_index);
+        }
+    }
+
+    fn layout(area: Rect) -> Rc<[Rect]> {
+        Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Length(3),
+                Constraint::Max(80),
+                Constraint::Length(10),
+            ],
+        )
+        .split(area)
+    }
+
+    fn open_link(&self) -> Option<UiCallback> {
+        let entry = self.entry.as_ref()?;
+        let link = entry.links.get(self.link_state.selected()?)?;
+
+        // The browser reports its own failures, there is nothing useful to show here
+        let _ = open::that_detached(link.href.clone());
+
+        None
+    }
+
+    // Opens the n-th link of the entry, counting from 1
+    pub fn open_link_number(&mut self, number: usize) -> bool {
+        let len = self.entry.as_ref().map_or(0, |entry| entry.links.len());
+        if number == 0 || number > len {
+            return false;
+        }
+
+        self.link_state.select(Some(number - 1));
+        self.selected_section = Some(Section::Links);
+        self.hovered_section = None;
+        self.open_link();
+
+        true
+    }
+
+    fn scroll_links(&mut self, down: bool) {
+        let Some(entry) = &self.entry else {
+            return;
+        };
+
+        if entry.links.is_empty() {
+            return;
+        }
+
+        let last = entry.links.len() - 1;
+        let index = match (self.link_state.selected(), down) {
+            (Some(index), true) if index < last => index + 1,
+            (Some(index), false) if index > 0 => index - 1,
+            (None, false) => last,
+            (Some(0), false) => last,
+            _ => 0,
+        };
+
+        self.link_state.select(Some(index));
+    }
+
+    pub fn update_description(&mut self, entry_id: i64, description: String) {
+        let Some(entry) = &mut self.entry else {
+            return;
+        };
+
+        if entry.id != entry_id {
+            return;
+        }
+
+        entry.update_description(description.clone());
+        self.line_index = 0;
+        if let Ok(description) = parse_html(description) {
+            self.description = Some(description);
+        }
+    }
 }
 
 impl View for Entry {
@@ -88,15 +185,7 @@ impl View for Entry {
             }
         };
 
-        let entry_layout = Layout::new(
-            Direction::Vertical,
-            [
-                Constraint::Length(3),
-                Constraint::Max(80),
-                Constraint::Length(10),
-            ],
-        )
-        .split(area);
+        let entry_layout = Self::layout(area);
 
         let Some(entry) = &self.entry else {
             BlockLabel::new()
@@ -116,20 +205,31 @@ impl View for Entry {
             return;
         };
 
-        BlockLabel::new()
-            .label(entry.title.clone())
-            .render(entry_layout[0], buf);
+        let label = match entry.tags.is_empty() {
+            true => entry.title.clone(),
+            false => format!("{} [{}]", entry.title, entry.tags.join(", ")),
+        };
+
+        BlockLabel::new().label(label).render(entry_layout[0], buf);
 
         match &self.description {
             Some(description) => {
+                let margin = Margin::new(
+                    (0.05 * entry_layout[1].width as f32) as u16,
+                    (0.05 * entry_layout[1].height as f32) as u16,
+                );
+                let text_area = entry_layout[1].inner(margin);
+                let progress = reading_progress(
+                    self.line_index,
+                    text_area.height,
+                    description.line_count(text_area.width),
+                );
+
                 BlockText::default()
-                    .title(None)
+                    .title(Some(format!("{progress}%")))
                     .paragraph(description.clone().scroll((self.line_index, 0)))
                     .style(content_style)
-                    .margin(Margin::new(
-                        (0.05 * entry_layout[1].width as f32) as u16,
-                        (0.05 * entry_layout[1].height as f32) as u16,
-                    ))
+                    .margin(margin)
                     .render(entry_layout[1], buf);
             }
             None => {
@@ -164,6 +264,18 @@ impl View for Entry {
 
     fn handle_key_event(&mut self, key: KeyEvent) -> Option<UiCallback> {
         match key.code {
+            KeyCode::Char('f') => {
+                let Some(entry) = &self.entry else {
+                    return None;
+                };
+                let entry_id = entry.id;
+
+                Some(Box::new(move |app| {
+                    app.dispatch(DataEvent::FetchArticle(entry_id))?;
+                    Ok(())
+                }))
+            }
+            KeyCode::Char('o') if self.selected_section == Some(Section::Links) => self.open_link(),
             KeyCode::Char('y') => {
                 let Some(entry) = &self.entry else {
                     return None;
@@ -318,12 +430,59 @@ impl View for Entry {
                     self.selected_section = None;
                 }
 
+                let position = self.unsaved_position();
                 return Some(Box::new(move |app| {
                     app.ui.back();
+                    if let Some((entry_id, line_index)) = position {
+                        app.ui.update_position(entry_id, line_index);
+                        app.dispatch(DataEvent::SavePosition(entry_id, line_index))?;
+                    }
                     Ok(())
                 }));
             }
             _ => None,
         }
     }
+
+    fn handle_mouse_event(
+        &mut self,
+        event: MouseEvent,
+        area: Rect,
+        double_click: bool,
+    ) -> Option<UiCallback> {
+        let entry_layout = Self::layout(area);
+        let position = Position::new(event.column, event.row);
+        let in_content = entry_layout[1].contains(position);
+        let in_links = entry_layout[2].contains(position);
+
+        match event.kind {
+            MouseEventKind::ScrollDown if in_content => {
+                self.line_index += 1;
+            }
+            MouseEventKind::ScrollUp if in_content => {
+                self.line_index = self.line_index.saturating_sub(1);
+            }
+            MouseEventKind::ScrollDown if in_links => self.scroll_links(true),
+            MouseEventKind::ScrollUp if in_links => self.scroll_links(false),
+            MouseEventKind::Down(MouseButton::Left) if in_content => {
+                self.selected_section = Some(Section::Content);
+                self.hovered_section = None;
+            }
+            MouseEventKind::Down(MouseButton::Left) if in_links => {
+                self.selected_section = Some(Section::Links);
+                self.hovered_section = None;
+
+                let len = self.entry.as_ref()?.links.len();
+                let index = list_row_index(&self.link_state, entry_layout[2], event.row, len)?;
+                self.link_state.select(Some(index));
+
+                if double_click {
+                    return self.open_link();
+                }
+            }
+            _ => {}
+        }
+
+        None
+    }
 }
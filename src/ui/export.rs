@@ -0,0 +1,80 @@
+use crate::{config::Settings, data::data::DataEvent, export::ExportFormat};
+
+use super::{
+    components::{centered_rect, BlockText, Popup},
+    UiCallback, View,
+};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::*,
+    widgets::{Paragraph, Wrap},
+};
+
+const WIDTH: u16 = 60;
+const HEIGHT: u16 = 4;
+
+pub struct Export {
+    entry_ids: Vec<i64>,
+    title: String,
+}
+
+impl Export {
+    pub fn new(entry_ids: Vec<i64>, title: String) -> Self {
+        Self { entry_ids, title }
+    }
+
+    fn export(&self, format: ExportFormat) -> Option<UiCallback> {
+        let entry_ids = self.entry_ids.clone();
+        let title = self.title.clone();
+
+        Some(Box::new(move |app| {
+            app.dispatch(DataEvent::Export(entry_ids.clone(), format, title.clone()))?;
+            app.ui.clear_marked();
+            app.ui.unset_popup();
+            Ok(())
+        }))
+    }
+}
+
+impl View for Export {
+    fn render(&self, area: Rect, buf: &mut Buffer, _config: &Settings) {
+        let count = match self.entry_ids.len() {
+            1 => "1 entry".to_string(),
+            n => format!("{n} entries"),
+        };
+
+        Popup::new(Some(
+            BlockText::default()
+                .title(Some(format!("Export {count}")))
+                .paragraph(
+                    Paragraph::new(vec![
+                        Line::from(self.title.clone()).bold(),
+                        Line::from("(m) Markdown  (h) HTML  (e) EPUB"),
+                    ])
+                    .alignment(Alignment::Center)
+                    .wrap(Wrap::default()),
+                ),
+        ))
+        .height(HEIGHT)
+        .width(WIDTH)
+        .render(area, buf);
+    }
+
+    fn bounds(&self, area: Rect) -> Rect {
+        centered_rect(area, WIDTH, HEIGHT)
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Option<UiCallback> {
+        match key.code {
+            KeyCode::Char('m') => self.export(ExportFormat::Markdown),
+            KeyCode::Char('h') => self.export(ExportFormat::Html),
+            KeyCode::Char('e') => self.export(ExportFormat::Epub),
+            KeyCode::Char('q') | KeyCode::Esc => Some(Box::new(move |app| {
+                app.ui.unset_popup();
+                Ok(())
+            })),
+            _ => None,
+        }
+    }
+}
@@ -2,10 +2,12 @@ use crate::app::ActiveBlock;
 use crate::app::Route;
 use crate::app::RouteId;
 use crate::config::Settings;
+use crate::data::data::DataEvent;
 use crate::prelude::FeedData;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyModifiers;
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{buffer::Buffer, layout::Rect, prelude::*, widgets::ListState};
 
 use super::components::*;
@@ -43,6 +45,58 @@ impl Feeds {
         self.feed_items = feeds;
     }
 
+    pub fn titles(&self) -> Vec<String> {
+        self.feed_items
+            .iter()
+            .map(|feed| feed.title.clone())
+            .collect()
+    }
+
+    pub fn title(&self, index: usize) -> Option<String> {
+        self.feed_items.get(index).map(|feed| feed.title.clone())
+    }
+
+    pub fn position(&self, title: &str) -> Option<usize> {
+        self.feed_items
+            .iter()
+            .position(|feed| feed.title.eq_ignore_ascii_case(title))
+    }
+
+    pub fn id(&self, index: usize) -> Option<i64> {
+        self.feed_items.get(index).map(|feed| feed.id)
+    }
+
+    pub fn position_id(&self, feed_id: i64) -> Option<usize> {
+        self.feed_items.iter().position(|feed| feed.id == feed_id)
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<FeedData> {
+        if index >= self.feed_items.len() {
+            return None;
+        }
+
+        let feed = self.feed_items.remove(index);
+        if index > 0 {
+            self.list_state.select(Some(index - 1));
+        } else {
+            self.list_state.select(None);
+        }
+
+        Some(feed)
+    }
+
+    pub fn insert(&mut self, index: usize, feed: FeedData) {
+        let index = index.min(self.feed_items.len());
+        self.feed_items.insert(index, feed);
+        self.list_state.select(Some(index));
+    }
+
+    pub fn select_index(&mut self, index: usize) {
+        if index < self.feed_items.len() {
+            self.list_state.select(Some(index));
+        }
+    }
+
     fn scroll_down(&mut self) -> Option<UiCallback> {
         if self.feed_items.is_empty() {
             return None;
@@ -86,6 +140,19 @@ impl Feeds {
         }));
     }
 
+    fn click_feed(&mut self, index: usize, double_click: bool) -> Option<UiCallback> {
+        self.list_state.select(Some(index));
+
+        Some(Box::new(move |app| {
+            app.ui.select_entries(index);
+            if double_click {
+                app.ui
+                    .set_current_route(Route::new(RouteId::Home, ActiveBlock::Entries));
+            }
+            Ok(())
+        }))
+    }
+
     fn select_feed(&mut self) -> Option<UiCallback> {
         return Some(Box::new(move |app| {
             app.ui
@@ -94,25 +161,53 @@ impl Feeds {
         }));
     }
 
-    fn delete_feed(&mut self) -> Option<UiCallback> {
-        if let Some(index) = self.list_state.selected() {
-            let feed_id = self.feed_items[index].id;
-            self.feed_items.remove(index);
+    fn export_feed(&mut self) -> Option<UiCallback> {
+        let index = self.list_state.selected()?;
+        let title = self.feed_items.get(index)?.title.clone();
 
-            if index > 0 {
-                self.list_state.select(Some(index - 1));
-            } else {
-                self.list_state.select(None);
-            }
+        Some(Box::new(move |app| {
+            app.ui.export_feed(index, title.clone());
+            Ok(())
+        }))
+    }
 
-            return Some(Box::new(move |app| {
-                app.ui.remove_entries(index);
-                app.dispatch(crate::data::data::DataEvent::DeleteFeed(feed_id.clone()))?;
-                Ok(())
-            }));
+    fn edit_feed(&mut self) -> Option<UiCallback> {
+        let feed = self.feed_items.get(self.list_state.selected()?)?.clone();
+
+        // Smart folders are edited in the config
+        if feed.is_smart_folder() {
+            return None;
         }
 
-        return None;
+        Some(Box::new(move |app| {
+            app.ui.edit_feed(feed.clone());
+            Ok(())
+        }))
+    }
+
+    fn delete_feed(&mut self) -> Option<UiCallback> {
+        let feed = self.feed_items.get(self.list_state.selected()?)?;
+
+        // Smart folders live in the config, not the database
+        if feed.is_smart_folder() {
+            return None;
+        }
+
+        let feed_id = feed.id;
+        let message = format!("Delete {} and all its entries?", feed.title);
+
+        Some(Box::new(move |app| {
+            app.ui.confirm(
+                message.clone(),
+                Box::new(move |app| {
+                    if let Some(previous) = app.ui.delete_feed(feed_id) {
+                        app.dispatch(DataEvent::DeleteFeed(previous))?;
+                    }
+                    Ok(())
+                }),
+            );
+            Ok(())
+        }))
     }
 }
 
@@ -126,7 +221,11 @@ impl View for Feeds {
         let feed_titles: Vec<String> = self
             .feed_items
             .iter()
-            .map(|feed| feed.title.clone())
+            .map(|feed| match (feed.is_smart_folder(), &feed.error) {
+                (true, _) => format!("» {} ({})", feed.title, feed.unread),
+                (false, Some(_)) => format!("! {}", feed.title),
+                (false, None) => feed.title.clone(),
+            })
             .collect();
 
         ItemList::new(&feed_titles)
@@ -153,6 +252,26 @@ impl View for Feeds {
             _ if key.code == KeyCode::Char('d') && key.modifiers == KeyModifiers::CONTROL => {
                 self.delete_feed()
             }
+            KeyCode::Char('e') => self.export_feed(),
+            KeyCode::Char('i') => self.edit_feed(),
+            _ => None,
+        }
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        event: MouseEvent,
+        area: Rect,
+        double_click: bool,
+    ) -> Option<UiCallback> {
+        match event.kind {
+            MouseEventKind::ScrollDown => self.scroll_down(),
+            MouseEventKind::ScrollUp => self.scroll_up(),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let index =
+                    list_row_index(&self.list_state, area, event.row, self.feed_items.len())?;
+                self.click_feed(index, double_click)
+            }
             _ => None,
         }
     }
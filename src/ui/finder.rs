@@ -0,0 +1,223 @@
+use crate::config::Settings;
+
+use super::{
+    components::{centered_rect, BlockText, Popup},
+    util::parse_hex,
+    UiCallback, View,
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::Paragraph};
+
+const WIDTH: u16 = 70;
+const HEIGHT: u16 = 18;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FinderTarget {
+    // Index into the feed list
+    Feed(usize),
+    // Index of the feed the entry is listed under and the entry id
+    Entry(usize, i64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinderItem {
+    pub target: FinderTarget,
+    pub label: String,
+}
+
+pub struct Finder {
+    input: Vec<char>,
+    items: Vec<FinderItem>,
+    results: Vec<usize>,
+    selected: usize,
+}
+
+impl Finder {
+    pub fn new(items: Vec<FinderItem>) -> Self {
+        let mut finder = Self {
+            input: Vec::new(),
+            items,
+            results: vec![],
+            selected: 0,
+        };
+        finder.search();
+        finder
+    }
+
+    fn search(&mut self) {
+        let pattern: String = self.input.iter().collect();
+        self.results = rank(&pattern, &self.items);
+        self.selected = 0;
+    }
+
+    fn jump(&self) -> Option<UiCallback> {
+        let target = self.items.get(*self.results.get(self.selected)?)?.target;
+
+        Some(Box::new(move |app| {
+            app.ui.unset_popup();
+            if let Some(callback) = app.ui.jump_to(target) {
+                callback(app)?;
+            }
+            Ok(())
+        }))
+    }
+
+    fn area(area: Rect) -> Rect {
+        centered_rect(area, WIDTH.min(area.width), HEIGHT.min(area.height))
+    }
+}
+
+impl View for Finder {
+    fn render(&self, area: Rect, buf: &mut Buffer, config: &Settings) {
+        let popup_area = Self::area(area);
+        let highlight = Style::default().bg(parse_hex(&config.colors.highlight));
+
+        // Two border rows, the input and a blank line
+        let rows = popup_area.height.saturating_sub(4) as usize;
+        let offset = (self.selected + 1).saturating_sub(rows);
+
+        let mut lines = vec![
+            Line::from(vec![
+                Span::raw("> "),
+                Span::raw(self.input.iter().collect::<String>()),
+                Span::raw("█"),
+            ]),
+            Line::default(),
+        ];
+
+        for (position, index) in self.results.iter().enumerate().skip(offset).take(rows) {
+            let item = &self.items[*index];
+            let marker = match item.target {
+                FinderTarget::Feed(_) => "#",
+                FinderTarget::Entry(..) => "-",
+            };
+            let line = Line::from(format!("{marker} {}", item.label));
+
+            lines.push(match position == self.selected {
+                true => line.style(highlight),
+                false => line,
+            });
+        }
+
+        Popup::new(Some(
+            BlockText::default()
+                .title(Some(format!(
+                    "Find ({}/{})",
+                    self.results.len(),
+                    self.items.len()
+                )))
+                .paragraph(Paragraph::new(lines)),
+        ))
+        .height(popup_area.height)
+        .width(popup_area.width)
+        .render(area, buf);
+    }
+
+    fn bounds(&self, area: Rect) -> Rect {
+        Self::area(area)
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Option<UiCallback> {
+        match key.code {
+            KeyCode::Esc => Some(Box::new(move |app| {
+                app.ui.unset_popup();
+                Ok(())
+            })),
+            KeyCode::Enter => self.jump(),
+            KeyCode::Down => {
+                if self.selected + 1 < self.results.len() {
+                    self.selected += 1;
+                }
+                None
+            }
+            KeyCode::Up => {
+                self.selected = self.selected.saturating_sub(1);
+                None
+            }
+            _ if key.modifiers == KeyModifiers::CONTROL => {
+                match key.code {
+                    KeyCode::Char('n') if self.selected + 1 < self.results.len() => {
+                        self.selected += 1
+                    }
+                    KeyCode::Char('p') => self.selected = self.selected.saturating_sub(1),
+                    _ => {}
+                }
+                None
+            }
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                self.search();
+                None
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.search();
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+// Indices of the items matching the pattern, best match first. Feeds win ties
+// over entries and the list order is kept otherwise.
+pub fn rank(pattern: &str, items: &[FinderItem]) -> Vec<usize> {
+    let mut scored: Vec<(i64, usize)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            let mut score = fuzzy_score(pattern, &item.label)?;
+            if let FinderTarget::Feed(_) = item.target {
+                score += 1;
+            }
+            Some((score, index))
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, index)| index).collect()
+}
+
+// Scores `text` when the characters of `pattern` appear in it in order, ignoring
+// case. Consecutive characters and matches at the start of words score higher,
+// gaps between matches lower.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let pattern: Vec<char> = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let mut score = 0;
+    let mut pattern_i = 0;
+    let mut last_match: Option<usize> = None;
+
+    for (i, c) in text.iter().enumerate() {
+        if pattern_i == pattern.len() {
+            break;
+        }
+
+        if !c.to_lowercase().eq(pattern[pattern_i].to_lowercase()) {
+            continue;
+        }
+
+        score += 1;
+
+        if i == 0 || !text[i - 1].is_alphanumeric() {
+            score += 8;
+        }
+
+        match last_match {
+            Some(last) if last + 1 == i => score += 5,
+            Some(last) => score -= (i - last - 1).min(5) as i64,
+            None => score -= (i / 4).min(5) as i64,
+        }
+
+        last_match = Some(i);
+        pattern_i += 1;
+    }
+
+    (pattern_i == pattern.len()).then_some(score)
+}
@@ -0,0 +1,98 @@
+use super::components::*;
+use super::messages::severity_style;
+use super::util::parse_hex;
+use super::{UiCallback, View};
+use crate::app::Severity;
+use crate::config::Settings;
+use crate::logging::recent_lines;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+use ratatui::prelude::*;
+use ratatui::widgets::{ListItem, ListState};
+
+// The log lines of this run, opened at the newest
+#[derive(Default)]
+pub struct Logs {
+    lines: Vec<String>,
+    list_state: ListState,
+}
+
+impl Logs {
+    pub fn reload(&mut self) {
+        self.lines = recent_lines();
+        self.list_state.select(self.lines.len().checked_sub(1));
+    }
+
+    fn scroll(&mut self, down: bool) {
+        let Some(last) = self.lines.len().checked_sub(1) else {
+            return;
+        };
+
+        let index = match (self.list_state.selected(), down) {
+            (Some(index), true) => (index + 1).min(last),
+            (Some(index), false) => index.saturating_sub(1),
+            (None, _) => last,
+        };
+        self.list_state.select(Some(index));
+    }
+
+    // env_logger writes `[<time> <LEVEL> <module>] <message>`
+    fn level(line: &str) -> Option<Severity> {
+        let header = line.split(']').next()?;
+        if header.contains(" ERROR ") {
+            Some(Severity::Error)
+        } else if header.contains(" WARN ") {
+            Some(Severity::Warning)
+        } else {
+            None
+        }
+    }
+}
+
+impl View for Logs {
+    fn render(&self, area: Rect, buf: &mut Buffer, config: &Settings) {
+        let items: Vec<ListItem> = match self.lines.is_empty() {
+            true => vec![ListItem::new("Nothing logged yet")],
+            false => self
+                .lines
+                .iter()
+                .map(|line| match Self::level(line) {
+                    Some(severity) => ListItem::new(line.clone()).style(severity_style(severity)),
+                    None => ListItem::new(line.clone()),
+                })
+                .collect(),
+        };
+
+        ItemList::new(&items)
+            .title(Some("Log, (r) to reload".to_string()))
+            .style(Style::default().fg(parse_hex(&config.colors.primary)))
+            .render(area, buf, &mut self.list_state.clone());
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Option<UiCallback> {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.scroll(true),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll(false),
+            KeyCode::Char('g') | KeyCode::Home => self.list_state.select_first(),
+            KeyCode::Char('G') | KeyCode::End => {
+                self.list_state.select(self.lines.len().checked_sub(1))
+            }
+            KeyCode::Char('r') => self.reload(),
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        event: MouseEvent,
+        _area: Rect,
+        _double_click: bool,
+    ) -> Option<UiCallback> {
+        match event.kind {
+            MouseEventKind::ScrollDown => self.scroll(true),
+            MouseEventKind::ScrollUp => self.scroll(false),
+            _ => {}
+        }
+        None
+    }
+}
@@ -0,0 +1,129 @@
+use crate::app::Severity;
+use crate::config::Settings;
+
+use super::components::*;
+use super::status::Message;
+use super::{UiCallback, View};
+
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+use ratatui::prelude::*;
+use ratatui::widgets::{ListItem, ListState};
+
+pub fn severity_style(severity: Severity) -> Style {
+    match severity {
+        Severity::Info => Style::default().fg(Color::Cyan),
+        Severity::Warning => Style::default().fg(Color::Yellow),
+        Severity::Error => Style::default().fg(Color::Red),
+    }
+}
+
+fn severity_label(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "info ",
+        Severity::Warning => "warn ",
+        Severity::Error => "error",
+    }
+}
+
+// The messages shown so far, newest first
+pub struct Messages {
+    messages: Vec<Message>,
+    list_state: ListState,
+}
+
+impl Messages {
+    pub fn new(history: &[Message]) -> Self {
+        let messages: Vec<Message> = history.iter().rev().cloned().collect();
+        let mut list_state = ListState::default();
+        if !messages.is_empty() {
+            list_state.select(Some(0));
+        }
+
+        Self {
+            messages,
+            list_state,
+        }
+    }
+
+    fn scroll(&mut self, down: bool) {
+        let Some(last) = self.messages.len().checked_sub(1) else {
+            return;
+        };
+
+        let index = match (self.list_state.selected(), down) {
+            (Some(index), true) => (index + 1).min(last),
+            (Some(index), false) => index.saturating_sub(1),
+            (None, _) => 0,
+        };
+        self.list_state.select(Some(index));
+    }
+
+    fn close() -> Option<UiCallback> {
+        Some(Box::new(move |app| {
+            app.ui.unset_popup();
+            Ok(())
+        }))
+    }
+}
+
+impl View for Messages {
+    fn render(&self, area: Rect, buf: &mut Buffer, _config: &Settings) {
+        let bounds = self.bounds(area);
+
+        let items: Vec<ListItem> = match self.messages.is_empty() {
+            true => vec![ListItem::new("No messages yet")],
+            false => self
+                .messages
+                .iter()
+                .map(|message| {
+                    ListItem::new(Line::from(vec![
+                        Span::raw(format!("{} ", message.time.format("%H:%M:%S"))).dim(),
+                        Span::styled(
+                            format!("{} ", severity_label(message.severity)),
+                            severity_style(message.severity),
+                        ),
+                        Span::raw(message.text.clone()),
+                    ]))
+                })
+                .collect(),
+        };
+
+        ratatui::widgets::Clear.render(bounds, buf);
+        ItemList::new(&items)
+            .title(Some(format!("Messages ({})", self.messages.len())))
+            .render(bounds, buf, &mut self.list_state.clone());
+    }
+
+    fn bounds(&self, area: Rect) -> Rect {
+        centered_rect(area, area.width * 4 / 5, area.height * 4 / 5)
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Option<UiCallback> {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.scroll(true);
+                None
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.scroll(false);
+                None
+            }
+            KeyCode::Char('q') | KeyCode::Esc => Self::close(),
+            _ => None,
+        }
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        event: MouseEvent,
+        _area: Rect,
+        _double_click: bool,
+    ) -> Option<UiCallback> {
+        match event.kind {
+            MouseEventKind::ScrollDown => self.scroll(true),
+            MouseEventKind::ScrollUp => self.scroll(false),
+            _ => {}
+        }
+        None
+    }
+}
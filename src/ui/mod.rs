@@ -1,13 +1,22 @@
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::{buffer::Buffer, layout::Rect};
 
 use crate::{app::App, config::Settings, AppResult};
 
 mod add;
+pub mod command;
 mod components;
+mod confirm;
+mod edit_feed;
 mod entries;
 mod entry;
+mod export;
 mod feeds;
+pub mod finder;
+mod logs;
+mod messages;
+mod stats;
+pub mod status;
 pub mod ui;
 pub mod util;
 
@@ -17,4 +26,19 @@ pub trait View {
     fn render(&self, area: Rect, buf: &mut Buffer, config: &Settings);
 
     fn handle_key_event(&mut self, key: KeyEvent) -> Option<UiCallback>;
+
+    // `area` is where the view was last rendered
+    fn handle_mouse_event(
+        &mut self,
+        _event: MouseEvent,
+        _area: Rect,
+        _double_click: bool,
+    ) -> Option<UiCallback> {
+        None
+    }
+
+    // The part of `area` the view draws over, popups only cover the middle of it
+    fn bounds(&self, area: Rect) -> Rect {
+        area
+    }
 }
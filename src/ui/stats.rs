@@ -0,0 +1,158 @@
+use super::components::*;
+use super::util::{format_size, parse_hex};
+use super::{UiCallback, View};
+use crate::config::Settings;
+use crate::data::data::DataEvent;
+use crate::data::stats::{Statistics, WEEKS};
+use chrono::Duration;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Bar, BarChart, BarGroup, Block, Borders, List, Sparkline};
+
+// How many of the busiest feeds are listed
+const MOST_ACTIVE: usize = 20;
+const READ_WIDTH: u16 = 6;
+
+#[derive(Default)]
+pub struct Stats {
+    stats: Option<Statistics>,
+}
+
+impl Stats {
+    pub fn update(&mut self, stats: Statistics) {
+        self.stats = Some(stats);
+    }
+
+    fn render_weeks(stats: &Statistics, area: Rect, buf: &mut Buffer, style: Style) {
+        let weekly = stats.weekly();
+        let bars: Vec<Bar> = weekly
+            .iter()
+            .enumerate()
+            .map(|(week, count)| {
+                let start = stats.taken - Duration::weeks((WEEKS - week) as i64);
+                Bar::default()
+                    .value(*count)
+                    .label(Line::from(start.format("%m-%d").to_string()))
+            })
+            .collect();
+
+        let inner_width = area.width.saturating_sub(2) / WEEKS as u16;
+
+        BarChart::default()
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Entries per week, last {WEEKS} weeks")),
+            )
+            .bar_width(inner_width.saturating_sub(1).max(1))
+            .bar_gap(1)
+            .bar_style(style)
+            .data(BarGroup::default().bars(&bars))
+            .render(area, buf);
+    }
+
+    fn render_most_active(stats: &Statistics, area: Rect, buf: &mut Buffer, style: Style) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Most active, entries per week and read");
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let feeds = stats.most_active(MOST_ACTIVE);
+        if feeds.is_empty() {
+            Line::from("Nothing new lately").render(inner, buf);
+            return;
+        }
+
+        for (row, feed) in feeds.iter().take(inner.height as usize).enumerate() {
+            let line = Rect::new(inner.x, inner.y + row as u16, inner.width, 1);
+            let columns = Layout::new(
+                Direction::Horizontal,
+                [
+                    Constraint::Min(10),
+                    Constraint::Length(WEEKS as u16 + 1),
+                    Constraint::Length(READ_WIDTH),
+                ],
+            )
+            .split(line);
+
+            Line::from(feed.title.clone()).render(columns[0], buf);
+            Sparkline::default()
+                .data(&feed.weekly)
+                .style(style)
+                .render(columns[1], buf);
+            Line::from(format!("{:>3.0}%", feed.read_ratio() * 100.0))
+                .alignment(Alignment::Right)
+                .render(columns[2], buf);
+        }
+    }
+
+    fn render_stale(stats: &Statistics, area: Rect, buf: &mut Buffer) {
+        let items: Vec<String> = stats
+            .stale()
+            .iter()
+            .map(|feed| match feed.last_entry {
+                Some(last) => format!("{} · {}", feed.title, last.format("%Y-%m-%d")),
+                None => format!("{} · never", feed.title),
+            })
+            .collect();
+
+        let list = List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Quiet for {}+ days", stats.stale_days)),
+        );
+        Widget::render(list, area, buf);
+    }
+}
+
+impl View for Stats {
+    fn render(&self, area: Rect, buf: &mut Buffer, config: &Settings) {
+        let style = Style::default().fg(parse_hex(&config.colors.secondary));
+
+        let Some(stats) = &self.stats else {
+            BlockLabel::new()
+                .label("Loading statistics...".to_string())
+                .render(area, buf);
+            return;
+        };
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Length(3),
+                Constraint::Length(12),
+                Constraint::Min(5),
+            ],
+        )
+        .split(area);
+
+        BlockLabel::new()
+            .label(format!(
+                "{} feeds · {} entries · {:.0}% read · database {}",
+                stats.feeds.len(),
+                stats.entries(),
+                stats.read_ratio() * 100.0,
+                format_size(stats.database_size)
+            ))
+            .render(layout[0], buf);
+
+        Self::render_weeks(stats, layout[1], buf, style);
+
+        let lists = Layout::new(
+            Direction::Horizontal,
+            [Constraint::Percentage(60), Constraint::Percentage(40)],
+        )
+        .split(layout[2]);
+
+        Self::render_most_active(stats, lists[0], buf, style);
+        Self::render_stale(stats, lists[1], buf);
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Option<UiCallback> {
+        match key.code {
+            KeyCode::Char('r') => Some(Box::new(|app| app.dispatch(DataEvent::Statistics))),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// The bottom bar. A new message shows as a toast for a few seconds, a spinner turns
+// while the data handler works, and the help line shows otherwise. Messages are kept
+// for the history popup, see `messages`.
+
+use super::messages::severity_style;
+use crate::app::Severity;
+use crate::time::{SystemTimeTick, Tick};
+use chrono::{DateTime, Local};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Paragraph};
+use std::time::{Duration, Instant};
+
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+const HISTORY_LEN: usize = 200;
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+// Milliseconds each spinner frame is shown
+const SPINNER_STEP: Tick = 80;
+const HELP: &str =
+    ": for commands, / to find, Ctrl+a to add feed, Ctrl+d to delete feed, (ESC/Q) to quit";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub severity: Severity,
+    pub text: String,
+    pub time: DateTime<Local>,
+}
+
+#[derive(Default)]
+pub struct StatusBar {
+    history: Vec<Message>,
+    toast_until: Option<Instant>,
+}
+
+impl StatusBar {
+    pub fn push(&mut self, severity: Severity, text: String) {
+        self.history.push(Message {
+            severity,
+            text,
+            time: Local::now(),
+        });
+        if self.history.len() > HISTORY_LEN {
+            self.history.remove(0);
+        }
+
+        self.toast_until = Some(Instant::now() + TOAST_DURATION);
+    }
+
+    // Oldest first
+    pub fn history(&self) -> &[Message] {
+        &self.history
+    }
+
+    // The latest message, while it is shown
+    pub fn toast(&self) -> Option<&Message> {
+        match self.toast_until {
+            Some(until) if Instant::now() < until => self.history.last(),
+            _ => None,
+        }
+    }
+
+    // `loading` is the progress of the running job, if there is one
+    pub fn render(&self, area: Rect, buf: &mut Buffer, loading: Option<&str>) {
+        let mut spans = vec![];
+
+        if loading.is_some() {
+            let frame = (Tick::now() / SPINNER_STEP) as usize % SPINNER.len();
+            spans.push(Span::raw(format!("{} ", SPINNER[frame])));
+        }
+
+        match (self.toast(), loading) {
+            (Some(message), _) => {
+                spans.push(Span::styled(
+                    message.text.clone(),
+                    severity_style(message.severity),
+                ));
+            }
+            (None, Some(progress)) => spans.push(Span::raw(progress.to_string())),
+            (None, None) => spans.push(Span::raw(HELP)),
+        }
+
+        Paragraph::new(Line::from(spans))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL))
+            .render(area, buf);
+    }
+}
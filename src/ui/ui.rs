@@ -1,28 +1,68 @@
 use super::add::Add;
+use super::command::CommandLine;
+use super::confirm::Confirm;
+use super::edit_feed::EditFeed;
 use super::entries::Entries;
 use super::entry::Entry as EntryView;
+use super::export::Export;
 use super::feeds::Feeds;
+use super::finder::{Finder, FinderItem, FinderTarget};
+use super::logs::Logs;
+use super::messages::Messages;
+use super::stats::Stats;
+use super::status::{Message, StatusBar};
 use super::View;
 use super::{components::*, UiCallback};
-use crate::app::{ActiveBlock, Route, RouteId};
+use crate::app::{ActiveBlock, Route, RouteId, Severity, UiState};
 use crate::config::Settings;
 use crate::data::data::DataEvent;
+use crate::data::stats::Statistics;
+use crate::export::ExportFormat;
 use crate::prelude::{EntryData, FeedData};
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Constraint, Direction, Layout, Position, Rect};
 use ratatui::prelude::*;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+// How long a deleted feed can be brought back before it is deleted from the database
+const UNDO_WINDOW: Duration = Duration::from_secs(10);
+
+// Where each view was drawn last frame, used to route mouse events
+#[derive(Debug, Default, Clone, Copy)]
+struct UiLayout {
+    content: Rect,
+    feeds: Option<Rect>,
+    entries: Option<Rect>,
+}
+
+// A feed taken out of the lists, but still in the database
+struct DeletedFeed {
+    index: usize,
+    feed: FeedData,
+    entries: Vec<EntryData>,
+    until: Instant,
+}
 
 pub struct Ui {
     navigation_stack: Vec<Route>,
-    pub error_msg: Option<String>,
     pub loading_msg: String,
     pub is_loading: bool,
     feeds: Feeds,
     entries: Entries,
     entry: EntryView,
+    stats: Stats,
+    logs: Logs,
+    status: StatusBar,
     popup: Option<Box<dyn View>>,
+    command_line: CommandLine,
     config: Settings,
+    layout: UiLayout,
+    last_click: Option<(Instant, Position)>,
+    state_restored: bool,
+    deleted_feed: Option<DeletedFeed>,
 }
 
 impl Ui {
@@ -34,14 +74,21 @@ impl Ui {
 
         Self {
             navigation_stack: vec![Route::default()],
-            error_msg: None,
             loading_msg: "Loading...".to_string(),
             is_loading: false,
             feeds,
             entries,
             entry,
+            stats: Stats::default(),
+            logs: Logs::default(),
+            status: StatusBar::default(),
             popup: None,
+            command_line: CommandLine::new(),
             config,
+            layout: UiLayout::default(),
+            last_click: None,
+            state_restored: false,
+            deleted_feed: None,
         }
     }
 
@@ -65,6 +112,10 @@ impl Ui {
         self.entries.update_entries(entries);
     }
 
+    pub fn select_entries(&mut self, index: usize) {
+        self.entries.set_index(index);
+    }
+
     pub fn next_entries(&mut self) {
         self.entries.next_index();
     }
@@ -73,8 +124,75 @@ impl Ui {
         self.entries.prev_index();
     }
 
-    pub fn remove_entries(&mut self, index: usize) {
-        self.entries.remove(index);
+    pub fn notify(&mut self, severity: Severity, message: String) {
+        self.status.push(severity, message);
+    }
+
+    pub fn messages(&self) -> &[Message] {
+        self.status.history()
+    }
+
+    pub fn open_messages(&mut self) {
+        self.popup = Some(Box::new(Messages::new(self.status.history())));
+    }
+
+    pub fn confirm(&mut self, message: String, action: UiCallback) {
+        self.popup = Some(Box::new(Confirm::new(message, action)));
+    }
+
+    // Takes the feed out of the lists until the undo window closes. Returns the feed
+    // deleted before, which can't be undone any more.
+    pub fn delete_feed(&mut self, feed_id: i64) -> Option<i64> {
+        let index = self.feeds.position_id(feed_id)?;
+        let feed = self.feeds.remove(index)?;
+        let entries = self.entries.remove(index);
+
+        self.deleted_feed
+            .replace(DeletedFeed {
+                index,
+                feed,
+                entries,
+                until: Instant::now() + UNDO_WINDOW,
+            })
+            .map(|deleted| deleted.feed.id)
+    }
+
+    pub fn undo_delete(&mut self) -> bool {
+        let Some(deleted) = self.deleted_feed.take() else {
+            return false;
+        };
+
+        self.feeds.insert(deleted.index, deleted.feed);
+        self.entries.insert(deleted.index, deleted.entries);
+        true
+    }
+
+    // Refreshed data still has the deleted feed, so it is taken out again
+    pub fn hide_deleted_feed(&mut self) {
+        let Some(deleted) = &mut self.deleted_feed else {
+            return;
+        };
+        let Some(index) = self.feeds.position_id(deleted.feed.id) else {
+            return;
+        };
+
+        if let Some(feed) = self.feeds.remove(index) {
+            deleted.index = index;
+            deleted.feed = feed;
+            deleted.entries = self.entries.remove(index);
+        }
+    }
+
+    // The deleted feed whose undo window has closed
+    pub fn expired_feed(&mut self) -> Option<i64> {
+        match &self.deleted_feed {
+            Some(deleted) if Instant::now() >= deleted.until => self.take_deleted_feed(),
+            _ => None,
+        }
+    }
+
+    pub fn take_deleted_feed(&mut self) -> Option<i64> {
+        self.deleted_feed.take().map(|deleted| deleted.feed.id)
     }
 
     pub fn update_feeds(&mut self, feeds: Vec<FeedData>) {
@@ -87,6 +205,224 @@ impl Ui {
         }
     }
 
+    pub fn update_entry_description(&mut self, entry_id: i64, description: String) {
+        self.entries
+            .update_description(entry_id, description.clone());
+        self.entry.update_description(entry_id, description);
+    }
+
+    pub fn update_position(&mut self, entry_id: i64, line_index: u16) {
+        self.entries.update_position(entry_id, line_index);
+        self.entry.update_position(entry_id, line_index);
+    }
+
+    // Where reading stopped in the last opened entry, if it wasn't saved yet
+    pub fn unsaved_position(&self) -> Option<(i64, u16)> {
+        self.entry.unsaved_position()
+    }
+
+    pub fn open_statistics(&mut self) {
+        if self.get_current_route().map(|route| &route.id) != Some(&RouteId::Statistics) {
+            self.set_current_route(Route::new(RouteId::Statistics, ActiveBlock::Statistics));
+        }
+    }
+
+    pub fn open_logs(&mut self) {
+        self.logs.reload();
+        if self.get_current_route().map(|route| &route.id) != Some(&RouteId::Logs) {
+            self.set_current_route(Route::new(RouteId::Logs, ActiveBlock::Logs));
+        }
+    }
+
+    pub fn update_statistics(&mut self, stats: Statistics) {
+        self.stats.update(stats);
+    }
+
+    pub fn clear_marked(&mut self) {
+        self.entries.clear_marked();
+    }
+
+    pub fn export_feed(&mut self, index: usize, title: String) {
+        let entry_ids = self.entries.entry_ids(index);
+        if !entry_ids.is_empty() {
+            self.popup = Some(Box::new(Export::new(entry_ids, title)));
+        }
+    }
+
+    pub fn export_entries(&mut self, entry_ids: Vec<i64>, title: String) {
+        self.popup = Some(Box::new(Export::new(entry_ids, title)));
+    }
+
+    // Exports the marked entries, or the feed whose entries are shown. Without a
+    // format the export popup asks for one.
+    pub fn export_current(&mut self, format: Option<ExportFormat>) -> Option<UiCallback> {
+        let (entry_ids, title) = match self.entries.marked().is_empty() {
+            true => {
+                let index = self.entries.index();
+                (self.entries.entry_ids(index), self.feeds.title(index)?)
+            }
+            false => (
+                self.entries.marked().to_vec(),
+                "Selected Entries".to_string(),
+            ),
+        };
+
+        if entry_ids.is_empty() {
+            return None;
+        }
+
+        let Some(format) = format else {
+            self.popup = Some(Box::new(Export::new(entry_ids, title)));
+            return None;
+        };
+
+        Some(Box::new(move |app| {
+            app.dispatch(DataEvent::Export(entry_ids.clone(), format, title.clone()))?;
+            app.ui.clear_marked();
+            Ok(())
+        }))
+    }
+
+    pub fn set_unread_only(&mut self, unread_only: Option<bool>) {
+        self.entries.set_unread_only(unread_only);
+    }
+
+    // Marks the named feed read, or the one whose entries are shown. Returns the
+    // entries to update in the database, `None` if there is no such feed.
+    pub fn mark_all_read(&mut self, title: Option<&str>) -> Option<Vec<i64>> {
+        let index = match title {
+            Some(title) => self.feeds.position(title)?,
+            None => self.entries.index(),
+        };
+
+        self.feeds.title(index)?;
+        Some(self.entries.mark_read(index))
+    }
+
+    // Opens the n-th link while reading an entry, the n-th entry of the shown feed otherwise
+    pub fn open_number(&mut self, number: usize) -> Option<UiCallback> {
+        let current_route = self
+            .get_current_route()
+            .unwrap_or(&Route::default())
+            .clone();
+
+        match current_route.id {
+            RouteId::Entry => match self.entry.open_link_number(number) {
+                true => Some(Box::new(|_| Ok(()))),
+                false => None,
+            },
+            RouteId::Home => self.entries.open(number),
+            RouteId::Statistics | RouteId::Logs => None,
+        }
+    }
+
+    pub fn go_to_feed(&mut self, title: &str) -> bool {
+        let Some(index) = self.feeds.position(title) else {
+            return false;
+        };
+
+        self.go_to_feed_index(index);
+        true
+    }
+
+    fn go_to_feed_index(&mut self, index: usize) {
+        self.feeds.select_index(index);
+        self.entries.set_index(index);
+        self.navigation_stack = vec![Route::default()];
+    }
+
+    pub fn open_finder(&mut self) {
+        let mut items = vec![];
+        let mut seen = HashSet::new();
+
+        for (index, title) in self.feeds.titles().into_iter().enumerate() {
+            items.push(FinderItem {
+                target: FinderTarget::Feed(index),
+                label: title,
+            });
+        }
+
+        // Smart folders come after the feeds, so their entries are already listed
+        for (index, group) in self.entries.groups().iter().enumerate() {
+            let feed = self.feeds.title(index).unwrap_or_default();
+            for entry in group.iter().filter(|entry| seen.insert(entry.id)) {
+                items.push(FinderItem {
+                    target: FinderTarget::Entry(index, entry.id),
+                    label: format!("{} · {}", entry.title, feed),
+                });
+            }
+        }
+
+        self.popup = Some(Box::new(Finder::new(items)));
+    }
+
+    pub fn jump_to(&mut self, target: FinderTarget) -> Option<UiCallback> {
+        match target {
+            FinderTarget::Feed(index) => {
+                self.go_to_feed_index(index);
+                None
+            }
+            FinderTarget::Entry(index, entry_id) => {
+                self.go_to_feed_index(index);
+                self.set_current_route(Route::new(RouteId::Home, ActiveBlock::Entries));
+                self.entries.open_id(entry_id)
+            }
+        }
+    }
+
+    // The state to save on exit. `None` until the saved state was restored, so quitting
+    // right after the start keeps it.
+    pub fn state(&self) -> Option<UiState> {
+        if !self.state_restored {
+            return None;
+        }
+
+        Some(UiState {
+            routes: self
+                .navigation_stack
+                .iter()
+                .filter(|route| !matches!(route.id, RouteId::Statistics | RouteId::Logs))
+                .cloned()
+                .collect(),
+            feed_id: self.feeds.id(self.entries.index()),
+            entry_id: self.entries.selected_id(),
+            line_index: self.entry.line_index(),
+            unread_only: self.entries.unread_only(),
+        })
+    }
+
+    // Feeds or entries deleted since are skipped, along with the routes showing them
+    pub fn restore_state(&mut self, state: UiState) {
+        self.state_restored = true;
+
+        if let Some(index) = state.feed_id.and_then(|id| self.feeds.position_id(id)) {
+            self.go_to_feed_index(index);
+        }
+        self.entries.set_unread_only(Some(state.unread_only));
+        let entry = state.entry_id.and_then(|id| self.entries.select_id(id));
+
+        let mut routes = vec![Route::default()];
+        for route in state.routes.into_iter().skip(1) {
+            match route.id {
+                RouteId::Home => routes.push(route),
+                RouteId::Entry => {
+                    let Some(entry) = entry.clone() else {
+                        break;
+                    };
+                    self.entry.set_entry(entry);
+                    self.entry.set_line_index(state.line_index);
+                    routes.push(route);
+                }
+                RouteId::Statistics | RouteId::Logs => {}
+            }
+        }
+        self.navigation_stack = routes;
+    }
+
+    pub fn edit_feed(&mut self, feed: FeedData) {
+        self.popup = Some(Box::new(EditFeed::new(feed)));
+    }
+
     pub fn unset_popup(&mut self) {
         self.popup = None;
     }
@@ -110,11 +446,16 @@ impl Ui {
                 }
                 _ => {}
             },
-            RouteId::Entry => {}
+            RouteId::Entry | RouteId::Statistics | RouteId::Logs => {}
         }
     }
 
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Option<UiCallback> {
+        if self.command_line.active {
+            let feed_titles = self.feeds.titles();
+            return self.command_line.handle_key_event(key, &feed_titles);
+        }
+
         match key {
             _ if key.code == KeyCode::Char('q') || key.code == KeyCode::Esc => {
                 if let Some(popup) = &mut self.popup {
@@ -135,6 +476,22 @@ impl Ui {
                     return None;
                 }
             }
+            _ if key.code == KeyCode::Char(':') && self.popup.is_none() => {
+                self.command_line.open();
+                None
+            }
+            _ if key.code == KeyCode::Char('/') && self.popup.is_none() => {
+                self.open_finder();
+                None
+            }
+            _ if key.code == KeyCode::Char('u')
+                && key.modifiers.is_empty()
+                && self.popup.is_none()
+                && self.deleted_feed.is_some() =>
+            {
+                self.undo_delete();
+                None
+            }
             _ if key.code == KeyCode::Char('a') && key.modifiers == KeyModifiers::CONTROL => {
                 self.popup = Some(Box::new(Add::new()));
                 return None;
@@ -162,10 +519,78 @@ impl Ui {
                         _ => None,
                     },
                     RouteId::Entry => self.entry.handle_key_event(key),
+                    RouteId::Statistics => self.stats.handle_key_event(key),
+                    RouteId::Logs => self.logs.handle_key_event(key),
                 }
             }
         }
     }
+
+    pub fn handle_mouse_event(&mut self, event: MouseEvent) -> Option<UiCallback> {
+        let position = Position::new(event.column, event.row);
+        let double_click = self.is_double_click(&event, position);
+        let clicked = matches!(event.kind, MouseEventKind::Down(_));
+
+        if let Some(popup) = &mut self.popup {
+            if clicked && !popup.bounds(self.layout.content).contains(position) {
+                self.unset_popup();
+                return None;
+            }
+            return popup.handle_mouse_event(event, self.layout.content, double_click);
+        }
+
+        let current_route = self
+            .get_current_route()
+            .unwrap_or(&Route::default())
+            .clone();
+
+        match current_route.id {
+            RouteId::Home => {
+                if let Some(area) = self.layout.feeds.filter(|area| area.contains(position)) {
+                    if clicked && current_route.active_block == ActiveBlock::Entries {
+                        self.back();
+                    }
+                    return self.feeds.handle_mouse_event(event, area, double_click);
+                }
+
+                if let Some(area) = self.layout.entries.filter(|area| area.contains(position)) {
+                    if clicked && current_route.active_block == ActiveBlock::Feeds {
+                        self.set_current_route(Route::new(RouteId::Home, ActiveBlock::Entries));
+                    }
+                    return self.entries.handle_mouse_event(event, area, double_click);
+                }
+
+                None
+            }
+            RouteId::Entry => {
+                self.entry
+                    .handle_mouse_event(event, self.layout.content, double_click)
+            }
+            RouteId::Statistics => None,
+            RouteId::Logs => self
+                .logs
+                .handle_mouse_event(event, self.layout.content, double_click),
+        }
+    }
+
+    fn is_double_click(&mut self, event: &MouseEvent, position: Position) -> bool {
+        if event.kind != MouseEventKind::Down(MouseButton::Left) {
+            return false;
+        }
+
+        let now = Instant::now();
+        let double_click = self
+            .last_click
+            .is_some_and(|(time, last)| last == position && now - time < DOUBLE_CLICK);
+
+        // A third click starts a new pair instead of counting as another double click
+        self.last_click = match double_click {
+            true => None,
+            false => Some((now, position)),
+        };
+
+        double_click
+    }
 }
 
 impl Widget for &mut Ui {
@@ -189,15 +614,22 @@ impl Widget for &mut Ui {
             .unwrap_or(&Route::default())
             .clone();
 
+        self.layout = UiLayout {
+            content: app_layout[1],
+            ..Default::default()
+        };
+
         match current_route.id {
             RouteId::Home => {
                 if area.height > (area.width as f32 * 0.5) as u16 {
                     match current_route.active_block {
                         ActiveBlock::Feeds => {
                             self.feeds.render(app_layout[1], buf, &self.config);
+                            self.layout.feeds = Some(app_layout[1]);
                         }
                         ActiveBlock::Entries => {
                             self.entries.render(app_layout[1], buf, &self.config);
+                            self.layout.entries = Some(app_layout[1]);
                         }
                         _ => {}
                     }
@@ -211,26 +643,41 @@ impl Widget for &mut Ui {
                     self.feeds.render(lists_section[0], buf, &self.config);
 
                     self.entries.render(lists_section[1], buf, &self.config);
-                }
 
-                if let Some(popup) = &self.popup {
-                    popup.render(app_layout[1], buf, &self.config);
+                    self.layout.feeds = Some(lists_section[0]);
+                    self.layout.entries = Some(lists_section[1]);
                 }
             }
 
             RouteId::Entry => {
                 self.entry.render(app_layout[1], buf, &self.config);
             }
+
+            RouteId::Statistics => {
+                self.stats.render(app_layout[1], buf, &self.config);
+            }
+
+            RouteId::Logs => {
+                self.logs.render(app_layout[1], buf, &self.config);
+            }
         }
 
-        if self.is_loading {
+        if let Some(popup) = &self.popup {
+            popup.render(app_layout[1], buf, &self.config);
+        }
+
+        if self.command_line.active {
+            self.command_line.render(app_layout[2], buf);
+        } else if self.deleted_feed.is_some() {
             BlockLabel::new()
-                .label(self.loading_msg.clone())
+                .label("Feed deleted — press u to undo".to_string())
                 .render(app_layout[2], buf);
         } else {
-            BlockLabel::new()
-                .label("Ctrl+a to add feed, Ctrl+d to delete feed, (ESC/Q) to quit".to_string())
-                .render(app_layout[2], buf);
+            self.status.render(
+                app_layout[2],
+                buf,
+                self.is_loading.then_some(self.loading_msg.as_str()),
+            );
         }
     }
 }
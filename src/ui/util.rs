@@ -222,3 +222,32 @@ pub fn parse_hex(hex: &String) -> Color {
         u8::from_str_radix(&hex[5..7], 16).unwrap(),
     )
 }
+
+// 1536 bytes reads 1.5 KiB
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+// How far through `lines` lines the reader is, in percent, seeing `height` lines
+// from `line_index` on
+pub fn reading_progress(line_index: u16, height: u16, lines: usize) -> u16 {
+    let seen = line_index as usize + height as usize;
+    if lines == 0 || seen >= lines {
+        return 100;
+    }
+
+    (seen * 100 / lines) as u16
+}
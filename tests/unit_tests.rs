@@ -1,19 +1,54 @@
 use core::panic;
-use std::{env::current_dir, fs::create_dir_all, time::Duration};
+use std::{collections::HashMap, env::current_dir, fs::create_dir_all, io::Write, time::Duration};
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use crabfeed::{
-    app::AppEvent,
-    config::{get_configuration, Settings},
+    app::{ActiveBlock, AppEvent, Route, RouteId, Severity, UiState},
+    cli::{parse_args, take_log_level, Command},
+    config::{
+        get_configuration, FeedAuth, FeedSettings, HttpSettings, Rule, RuleAction, Secret,
+        Settings, SmartFolder, SyncSettings,
+    },
     data::{
+        article::extract_article,
         data::{self, DataEvent},
-        db::{connect, select_entry},
+        db::{
+            connect, insert_article, insert_feed, mark_entry_read, select_all_entries,
+            select_all_feeds, select_article, select_duplicate_ids, select_entry, select_feed,
+            select_query_entries, select_sync_feeds, select_sync_queue, update_feed_properties,
+            update_feed_redirect, update_feed_title, update_feed_url,
+        },
+        duplicates::{canonical_link, collapse_duplicates},
+        encoding::{decode_feed, detect_encoding},
+        fetch::{
+            build_client, build_sync_client, fetch, fetch_authenticated, Credentials, HttpClients,
+            MAX_REDIRECTS,
+        },
+        greader::{long_id, short_id},
+        hooks::{prepare_hooks, run_hooks},
+        query::{EntryQuery, QueryParam},
+        rules::{rule_actions, RuleFeed},
+        source::{download, filter_feed, FeedSource},
+        stats::{statistics, WEEKS},
+        sync::{queue_change, sync, SyncAction, SyncBackend},
+    },
+    error::Error,
+    export::{render_html, render_markdown, to_xhtml, ExportEntry, ExportFormat},
+    logging::{log_level, RotatingFile},
+    prelude::{EntryData, FeedData, FeedProperties},
+    serve::{render_atom, route, slug, Planet},
+    ui::{
+        command::{completions, Command as UiCommand, UiOption},
+        finder::{fuzzy_score, rank, FinderItem, FinderTarget},
+        ui::Ui,
+        util::{format_size, parse_hex, reading_progress},
     },
-    ui::util::parse_hex,
 };
+use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use env_logger::Target;
-use log::{debug, info};
-use ratatui::style::Color;
+use log::{debug, info, LevelFilter};
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::time::sleep;
 
 #[tokio::test]
@@ -26,7 +61,7 @@ async fn data_is_refreshed() {
     let db_url = get_test_database_url();
 
     // Handle a reload event
-    data::handle_event(db_url, DataEvent::Refresh, sender)
+    data::handle_event(&get_test_config(&db_url), DataEvent::Refresh, sender)
         .await
         .expect("Failed to handle ReloadFeeds event");
 
@@ -85,9 +120,13 @@ async fn feed_is_added() {
 
     for feed in feed_list {
         // Handle an insertion event
-        data::handle_event(db_url.clone(), DataEvent::AddFeed(feed), sender.clone())
-            .await
-            .expect("Failed to handle AddFeed event");
+        data::handle_event(
+            &get_test_config(&db_url),
+            DataEvent::AddFeed(feed),
+            sender.clone(),
+        )
+        .await
+        .expect("Failed to handle AddFeed event");
 
         sleep(Duration::from_secs(2)).await;
 
@@ -137,9 +176,13 @@ async fn feed_is_deleted() {
 
     for feed in feed_list {
         // Handle an insertion event
-        data::handle_event(db_url.clone(), DataEvent::AddFeed(feed), sender.clone())
-            .await
-            .expect("Failed to handle AddFeed event");
+        data::handle_event(
+            &get_test_config(&db_url),
+            DataEvent::AddFeed(feed),
+            sender.clone(),
+        )
+        .await
+        .expect("Failed to handle AddFeed event");
 
         sleep(Duration::from_secs(2)).await;
 
@@ -168,9 +211,13 @@ async fn feed_is_deleted() {
         sleep(Duration::from_secs(2)).await;
 
         // Handle a reload event
-        data::handle_event(db_url.clone(), DataEvent::Refresh, sender.clone())
-            .await
-            .expect("Failed to handle ReloadFeeds event");
+        data::handle_event(
+            &get_test_config(&db_url),
+            DataEvent::Refresh,
+            sender.clone(),
+        )
+        .await
+        .expect("Failed to handle ReloadFeeds event");
 
         sleep(Duration::from_secs(2)).await;
 
@@ -212,7 +259,7 @@ async fn feed_is_deleted() {
 
         // Handle delete event
         data::handle_event(
-            db_url.clone(),
+            &get_test_config(&db_url),
             DataEvent::DeleteFeed(feed_ids[0]),
             sender.clone(),
         )
@@ -258,7 +305,7 @@ async fn entry_is_marked_read() {
 
     // Handle an insertion event
     data::handle_event(
-        db_url.clone(),
+        &get_test_config(&db_url),
         DataEvent::AddFeed("https://archlinux.org/feeds/news/".to_string()),
         sender.clone(),
     )
@@ -292,9 +339,13 @@ async fn entry_is_marked_read() {
     sleep(Duration::from_secs(2)).await;
 
     // Handle a reload event
-    data::handle_event(db_url.clone(), DataEvent::Refresh, sender.clone())
-        .await
-        .expect("Failed to handle ReloadFeeds event");
+    data::handle_event(
+        &get_test_config(&db_url),
+        DataEvent::Refresh,
+        sender.clone(),
+    )
+    .await
+    .expect("Failed to handle ReloadFeeds event");
 
     sleep(Duration::from_secs(2)).await;
 
@@ -341,7 +392,7 @@ async fn entry_is_marked_read() {
     let entry_id = entries[0].id;
 
     data::handle_event(
-        db_url.clone(),
+        &get_test_config(&db_url),
         DataEvent::ReadEntry(entry_id),
         sender.clone(),
     )
@@ -373,6 +424,1440 @@ async fn entry_is_marked_read() {
     assert_eq!(entry.read, Some(true));
 }
 
+#[tokio::test]
+async fn custom_feed_title_is_pinned() {
+    init_logger();
+    let db_url = get_test_database_url();
+    let conn = &mut connect(db_url)
+        .await
+        .expect("Failed to connect to database");
+
+    let rss = r#"<?xml version="1.0"?>
+        <rss version="2.0"><channel>
+            <title>Remote Title</title>
+            <link>https://example.com/</link>
+            <item><title>First</title><guid>1</guid></item>
+        </channel></rss>"#;
+    let feed = feed_rs::parser::parse(rss.as_bytes()).expect("Failed to parse feed");
+
+    let (feed_id, _) = insert_feed(conn, feed, &[])
+        .await
+        .expect("Failed to insert feed");
+
+    let properties = FeedProperties {
+        title: Some("My Title".to_string()),
+        url: "https://example.com/feed.xml".to_string(),
+        notes: Some("Weekly".to_string()),
+    };
+    update_feed_properties(conn, &feed_id, &properties)
+        .await
+        .expect("Failed to update feed properties");
+    update_feed_title(conn, &feed_id, "Renamed Remotely".to_string())
+        .await
+        .expect("Failed to update feed title");
+
+    let feed = select_feed(conn, &feed_id)
+        .await
+        .expect("Failed to select feed");
+    assert_eq!(feed.display_title(), Some("My Title".to_string()));
+    assert_eq!(feed.title, Some("Renamed Remotely".to_string()));
+    assert_eq!(feed.url, Some("https://example.com/feed.xml".to_string()));
+    assert_eq!(feed.notes, Some("Weekly".to_string()));
+
+    let query: EntryQuery = r#"feed:"My Title""#.parse().expect("Failed to parse query");
+    let entries = select_query_entries(conn, &query)
+        .await
+        .expect("Failed to select entries");
+    assert!(entries.iter().any(|entry| entry.feed_id == feed_id));
+}
+
+#[tokio::test]
+async fn permanent_redirects_are_recorded() {
+    let base = mock_server(|request| {
+        let response = match request.split_whitespace().nth(1).unwrap_or("/") {
+            "/old" => "HTTP/1.1 301 Moved Permanently\r\nLocation: /older\r\n",
+            "/older" => "HTTP/1.1 308 Permanent Redirect\r\nLocation: /feed\r\n",
+            "/temporary" => "HTTP/1.1 302 Found\r\nLocation: /old\r\n",
+            "/loop" => "HTTP/1.1 301 Moved Permanently\r\nLocation: /loop\r\n",
+            _ => "HTTP/1.1 200 OK\r\n",
+        };
+        format!("{response}Content-Length: 0\r\n\r\n").into_bytes()
+    })
+    .await;
+    let client = build_client(&HttpSettings::default()).expect("Failed to build client");
+
+    let fetched = fetch(&client, &format!("{base}/old"))
+        .await
+        .expect("Failed to follow redirects");
+    assert_eq!(fetched.moved_to, Some(format!("{base}/feed")));
+
+    let fetched = fetch(&client, &format!("{base}/temporary"))
+        .await
+        .expect("Failed to follow redirects");
+    assert_eq!(fetched.moved_to, None);
+
+    let fetched = fetch(&client, &format!("{base}/feed"))
+        .await
+        .expect("Failed to fetch feed");
+    assert_eq!(fetched.moved_to, None);
+
+    let error = fetch(&client, &format!("{base}/loop")).await.err();
+    assert!(error.is_some_and(|e| e.to_string().contains(&MAX_REDIRECTS.to_string())));
+}
+
+#[tokio::test]
+async fn feed_settings_follow_redirects() {
+    let conn = &mut connect(get_test_database_url())
+        .await
+        .expect("Failed to connect to database");
+
+    let xml = "<feed xmlns=\"http://www.w3.org/2005/Atom\"><title>Wanderer</title>\
+        <id>urn:wanderer</id><updated>2024-05-01T12:00:00Z</updated></feed>";
+    let feed = feed_rs::parser::parse(xml.as_bytes()).expect("Failed to parse feed");
+    let (feed_id, _) = insert_feed(conn, feed, &[])
+        .await
+        .expect("Failed to insert feed");
+
+    // Moving twice still remembers where the feed was subscribed
+    let urls = [
+        "https://a.example.com/feed",
+        "https://b.example.com/feed",
+        "https://c.example.com/feed",
+    ];
+    for moved in urls.windows(2) {
+        update_feed_redirect(conn, &feed_id, moved[0].to_string(), moved[1].to_string())
+            .await
+            .expect("Failed to update feed redirect");
+    }
+    let feed = select_feed(conn, &feed_id).await.unwrap();
+    assert_eq!(feed.url.as_deref(), Some(urls[2]));
+    assert_eq!(feed.redirected_from.as_deref(), Some(urls[0]));
+
+    let mut config = Settings::default();
+    config.feeds.push(FeedSettings {
+        url: urls[0].to_string(),
+        full_text: true,
+        ..Default::default()
+    });
+    config.follow_redirect(urls[0], urls[2]);
+
+    assert!(config
+        .feed_settings(urls[2])
+        .is_some_and(|settings| settings.full_text));
+}
+
+#[tokio::test]
+async fn http_settings_are_applied() {
+    let base = mock_server(|request| {
+        let request = request.to_lowercase();
+        let status = match request.contains("user-agent: feed-bot")
+            && request.contains("x-api-key: secret")
+            && request.contains("accept-language: de")
+        {
+            true => "200 OK",
+            false => "400 Bad Request",
+        };
+        format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n").into_bytes()
+    })
+    .await;
+
+    let global = HttpSettings {
+        user_agent: Some("feed-bot".to_string()),
+        headers: HashMap::from([("Accept-Language".to_string(), "en".to_string())]),
+        ..Default::default()
+    };
+    let overrides = HttpSettings {
+        total_timeout: Some(5),
+        headers: HashMap::from([
+            ("Accept-Language".to_string(), "de".to_string()),
+            ("X-Api-Key".to_string(), "secret".to_string()),
+        ]),
+        ..Default::default()
+    };
+
+    let merged = global.merge(&overrides);
+    assert_eq!(merged.user_agent, Some("feed-bot".to_string()));
+    assert_eq!(merged.total_timeout, Some(5));
+
+    let client = build_client(&merged).expect("Failed to build client");
+    let fetched = fetch(&client, &format!("{base}/feed"))
+        .await
+        .expect("Failed to fetch feed");
+    assert!(fetched.response.status().is_success());
+
+    let invalid = HttpSettings {
+        proxy: Some("not a proxy".to_string()),
+        ..Default::default()
+    };
+    assert!(build_client(&invalid).is_err());
+}
+
+#[tokio::test]
+async fn feeds_are_fetched_with_credentials() {
+    let base = mock_server(|request| {
+        let request = request.to_lowercase();
+        let status = match request.split_whitespace().nth(1).unwrap_or("/") {
+            "/basic" if request.contains("authorization: basic bwu6ahvudgvymg==") => "200 OK",
+            "/bearer" if request.contains("authorization: bearer token123") => "200 OK",
+            "/forbidden" => "403 Forbidden",
+            _ => "401 Unauthorized",
+        };
+        format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n").into_bytes()
+    })
+    .await;
+    let client = build_client(&HttpSettings::default()).expect("Failed to build client");
+
+    let basic = FeedAuth::Basic {
+        username: "me".to_string(),
+        password: Secret::Plain("hunter2".to_string()),
+    };
+    let credentials = Credentials::resolve(&basic)
+        .await
+        .expect("Failed to resolve credentials");
+    assert!(
+        fetch_authenticated(&client, &format!("{base}/basic"), Some(&credentials))
+            .await
+            .is_ok()
+    );
+    assert!(!format!("{basic:?}").contains("hunter2"));
+
+    let bearer = FeedAuth::Bearer {
+        token: Secret::Command {
+            command: "printf 'token123\\nsecond line'".to_string(),
+        },
+    };
+    let credentials = Credentials::resolve(&bearer)
+        .await
+        .expect("Failed to resolve credentials");
+    assert!(
+        fetch_authenticated(&client, &format!("{base}/bearer"), Some(&credentials))
+            .await
+            .is_ok()
+    );
+
+    let missing = FeedAuth::Bearer {
+        token: Secret::Env {
+            env: "CRABFEED_TEST_MISSING_TOKEN".to_string(),
+        },
+    };
+    assert!(Credentials::resolve(&missing).await.is_err());
+
+    let error = fetch(&client, &format!("{base}/basic")).await.err();
+    assert!(matches!(error, Some(Error::Unauthorized(_))));
+    let error = fetch(&client, &format!("{base}/forbidden")).await.err();
+    assert!(error.is_some_and(|e| e.to_string() == "Authentication failed (403 Forbidden)"));
+}
+
+#[tokio::test]
+async fn feeds_are_read_from_files_and_commands() {
+    let config = Settings::default();
+    let clients = HttpClients::new(&config).expect("Failed to build clients");
+    let rss =
+        r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Local</title></channel></rss>"#;
+
+    let path = std::env::temp_dir().join("crabfeed-local-feed.xml");
+    std::fs::write(&path, rss).expect("Failed to write feed file");
+    let file_url = format!("file://{}", path.display());
+    assert_eq!(
+        FeedSource::parse(&file_url).unwrap(),
+        FeedSource::File(path.clone())
+    );
+
+    let from_file = download(&config, &clients, &file_url)
+        .await
+        .expect("Failed to read feed file");
+    assert_eq!(from_file.body, rss.as_bytes());
+
+    let command_url = format!("exec: cat {}", path.display());
+    assert_eq!(
+        FeedSource::parse(&command_url).unwrap(),
+        FeedSource::Command(format!("cat {}", path.display()))
+    );
+    let from_command = download(&config, &clients, &command_url)
+        .await
+        .expect("Failed to run feed command");
+    assert_eq!(from_command.body, rss.as_bytes());
+
+    let error = download(&config, &clients, "exec:echo broken >&2; exit 3")
+        .await
+        .err();
+    assert!(matches!(error, Some(Error::Command(ref reason)) if reason.ends_with("broken")));
+    assert!(FeedSource::parse("exec:").is_err());
+    assert!(download(&config, &clients, "file:///does/not/exist.xml")
+        .await
+        .is_err());
+
+    std::fs::remove_file(path).expect("Failed to remove feed file");
+}
+
+#[tokio::test]
+async fn feeds_are_imported_from_stdin() {
+    init_logger();
+    let db_url = get_test_database_url();
+    let config = get_test_config(&db_url);
+
+    assert_eq!(
+        parse_args(["import"].map(String::from)).ok(),
+        Some(Command::Import)
+    );
+    assert!(parse_args(["import", "feed.xml"].map(String::from)).is_err());
+
+    // Latin-1, as a generator writing to a pipe might
+    let document = |items: &str| -> Vec<u8> {
+        format!(
+            r#"<?xml version="1.0" encoding="ISO-8859-1"?>
+            <rss version="2.0"><channel><title>Piped Café</title>{items}</channel></rss>"#
+        )
+        .chars()
+        .map(|c| c as u8)
+        .collect()
+    };
+    let first = r#"<item><title>First</title><guid>1</guid></item>"#;
+    let second = r#"<item><title>Second</title><guid>2</guid></item>"#;
+
+    let title = data::import_feed(&config, document(first))
+        .await
+        .expect("Failed to import feed");
+    assert_eq!(title, "Piped Caf\u{e9}");
+
+    // The next document updates the same feed
+    data::import_feed(&config, document(&format!("{first}{second}")))
+        .await
+        .expect("Failed to import feed again");
+
+    let conn = &mut connect(db_url)
+        .await
+        .expect("Failed to connect to database");
+    let feeds = select_all_feeds(conn)
+        .await
+        .expect("Failed to select feeds");
+    assert_eq!(feeds.len(), 1);
+    assert_eq!(feeds[0].url, None);
+    let entries = select_all_entries(conn, &feeds[0].id)
+        .await
+        .expect("Failed to select entries");
+    assert_eq!(entries.len(), 2);
+
+    assert!(data::import_feed(&config, b"not a feed".to_vec())
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn feeds_are_piped_through_filters() {
+    let broken = "<rss version=\"2.0\"><channel><title>Broken &nbsp; Feed</title></channel></rss>";
+    let path = std::env::temp_dir().join("crabfeed-filtered-feed.xml");
+    std::fs::write(&path, broken).expect("Failed to write feed file");
+    let url = format!("file://{}", path.display());
+
+    let mut config = Settings::default();
+    config.feeds.push(FeedSettings {
+        url: url.clone(),
+        filter: Some("sed 's/&nbsp;/ /'".to_string()),
+        ..Default::default()
+    });
+    let clients = HttpClients::new(&config).expect("Failed to build clients");
+
+    let filtered = download(&config, &clients, &url)
+        .await
+        .expect("Failed to filter feed");
+    let feed = feed_rs::parser::parse(filtered.body.as_slice()).expect("Failed to parse feed");
+    assert!(feed
+        .title
+        .is_some_and(|title| title.content.starts_with("Broken")));
+
+    config.feeds[0].filter = Some("echo 'not xml' >&2; exit 1".to_string());
+    let error = download(&config, &clients, &url).await.err();
+    assert!(error.is_some_and(|e| e.to_string().starts_with("Command failed: filter")));
+
+    let unread = filter_feed("head -c 4", vec![b'x'; 1 << 20])
+        .await
+        .expect("Failed to filter large input");
+    assert_eq!(unread, b"xxxx");
+
+    std::fs::remove_file(path).expect("Failed to remove feed file");
+}
+
+#[test]
+fn feed_encoding_follows_the_prolog() {
+    // "Café" in ISO-8859-1, served with a header claiming UTF-8
+    let mut latin1 =
+        br#"<?xml version="1.0" encoding="ISO-8859-1"?><rss version="2.0"><channel><title>Caf"#
+            .to_vec();
+    latin1.extend_from_slice(&[0xe9]);
+    latin1.extend_from_slice(b"</title></channel></rss>");
+
+    assert_eq!(
+        detect_encoding(&latin1, Some("utf-8")).name(),
+        "windows-1252"
+    );
+    let decoded = decode_feed(latin1, Some("utf-8"));
+    assert!(
+        String::from_utf8_lossy(&decoded).starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#)
+    );
+    let feed = feed_rs::parser::parse(decoded.as_slice()).expect("Failed to parse feed");
+    assert_eq!(feed.title.unwrap().content, "Café");
+
+    // Without a prolog the server's charset is used
+    let undeclared = [b"<rss>Caf".as_slice(), &[0xe9], b"</rss>"].concat();
+    assert_eq!(
+        String::from_utf8(decode_feed(undeclared, Some("iso-8859-1"))).unwrap(),
+        "<rss>Café</rss>"
+    );
+
+    // A byte order mark beats everything
+    let bom = [
+        &[0xef, 0xbb, 0xbf],
+        br#"<?xml version="1.0" encoding="ISO-8859-1"?>"#.as_slice(),
+    ]
+    .concat();
+    assert_eq!(detect_encoding(&bom, Some("iso-8859-1")).name(), "UTF-8");
+}
+
+#[tokio::test]
+async fn responses_are_decompressed_and_limited() {
+    let base = mock_server(|_| {
+        // A gzipped `<rss version="2.0"><channel><title>Zipped</title></channel></rss>`
+        const GZIPPED: [u8; 72] = [
+            31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 179, 41, 42, 46, 86, 40, 75, 45, 42, 206, 204, 207,
+            179, 85, 50, 210, 51, 80, 178, 179, 73, 206, 72, 204, 203, 75, 205, 177, 179, 41, 201,
+            44, 201, 73, 181, 139, 202, 44, 40, 72, 77, 177, 209, 135, 240, 108, 244, 225, 210,
+            250, 64, 189, 118, 0, 33, 174, 88, 204, 65, 0, 0, 0,
+        ];
+
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n",
+            GZIPPED.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(&GZIPPED);
+        response
+    })
+    .await;
+    let url = format!("{base}/feed");
+
+    let mut config = Settings::default();
+    let clients = HttpClients::new(&config).expect("Failed to build clients");
+    let fetched = download(&config, &clients, &url)
+        .await
+        .expect("Failed to fetch compressed feed");
+    let feed = feed_rs::parser::parse(fetched.body.as_slice()).expect("Failed to parse feed");
+    assert_eq!(feed.title.unwrap().content, "Zipped");
+
+    // The limit applies to the decompressed body
+    config.http.max_response_size = Some(64);
+    let clients = HttpClients::new(&config).expect("Failed to build clients");
+    let error = download(&config, &clients, &url).await.err();
+    assert!(matches!(error, Some(Error::ResponseTooLarge(64))));
+}
+
+#[tokio::test]
+async fn stalled_bodies_time_out() {
+    init_logger();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind mock server");
+    let url = format!("http://{}/feed", listener.local_addr().unwrap());
+
+    // Sends the start of the body, then goes quiet while keeping the connection open
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.expect("Failed to accept");
+        let mut request = vec![0; 16 * 1024];
+        let _ = stream.read(&mut request).await;
+        let _ = stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n<rss>")
+            .await;
+        sleep(Duration::from_secs(10)).await;
+    });
+
+    let mut config = Settings::default();
+    config.http.read_timeout = Some(1);
+    let clients = HttpClients::new(&config).expect("Failed to build clients");
+    let error = download(&config, &clients, &url).await.err();
+    assert!(matches!(error, Some(Error::ReadTimeout(1))));
+}
+
+#[tokio::test]
+async fn entries_are_synced_with_google_reader() {
+    init_logger();
+    let conn = &mut connect(get_test_database_url())
+        .await
+        .expect("Failed to connect to database");
+
+    let base = mock_server(|request| {
+        let line = request.lines().next().unwrap_or_default();
+        let path = line.split_whitespace().nth(1).unwrap_or("/");
+        let body = if let Some(rest) = path.strip_prefix("/old-api/") {
+            // The server moved, logins included
+            return format!(
+                "HTTP/1.1 308 Permanent Redirect\r\nLocation: /api/{rest}\r\nContent-Length: 0\r\n\r\n"
+            )
+            .into_bytes();
+        } else if line.contains("/accounts/ClientLogin") {
+            "SID=unused\nLSID=unused\nAuth=abc123\n".to_string()
+        } else if !request.contains("GoogleLogin auth=abc123") {
+            return b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\n\r\n".to_vec();
+        } else if line.contains("/subscription/list") {
+            r#"{"subscriptions": [
+                {"id": "feed/1", "title": "Rust Blog", "url": "https://blog.rust-lang.org/feed.xml",
+                 "categories": [{"id": "user/-/label/Tech", "label": "Tech"}]},
+                {"id": "feed/2", "title": "Cats", "url": "https://cats.example.com/rss"}
+            ]}"#
+            .to_string()
+        } else if line.contains("/stream/contents/") {
+            r#"{"items": [
+                {"id": "tag:google.com,2005:reader/item/000000000000001f", "title": "First Post",
+                 "published": 1714560000, "canonical": [{"href": "https://blog.rust-lang.org/first"}],
+                 "summary": {"content": "<p>Hello</p>"}, "origin": {"streamId": "feed/1"}},
+                {"id": "tag:google.com,2005:reader/item/0000000000000020", "title": "Second Post",
+                 "alternate": [{"href": "https://cats.example.com/second"}],
+                 "origin": {"streamId": "feed/2"}}
+            ]}"#
+            .to_string()
+        } else if line.contains("/stream/items/ids") && line.contains("starred") {
+            r#"{"itemRefs": [{"id": "32"}]}"#.to_string()
+        } else if line.contains("/stream/items/ids") && line.contains("c=more") {
+            r#"{"itemRefs": [{"id": "31"}]}"#.to_string()
+        } else if line.contains("/stream/items/ids") {
+            // The unread ids come in two pages
+            r#"{"itemRefs": [], "continuation": "more"}"#.to_string()
+        } else if line.contains("/token") || line.contains("/edit-tag") {
+            "OK".to_string()
+        } else {
+            return b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec();
+        };
+
+        format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}", body.len()).into_bytes()
+    })
+    .await;
+
+    let settings = SyncSettings::GoogleReader {
+        url: format!("{base}/old-api/"),
+        username: "me".to_string(),
+        password: Secret::Plain("secret".to_string()),
+    };
+    let client = build_sync_client(&HttpSettings::default()).expect("Failed to build client");
+    let mut backend = SyncBackend::connect(&settings, client.clone())
+        .await
+        .expect("Failed to log in");
+
+    let report = sync(conn, &mut backend, &[]).await.expect("Failed to sync");
+    assert_eq!((report.pushed, report.feeds, report.entries), (0, 2, 2));
+
+    let feeds = select_all_feeds(conn)
+        .await
+        .expect("Failed to select feeds");
+    let rust = feeds
+        .iter()
+        .find(|feed| feed.url.as_deref() == Some("https://blog.rust-lang.org/feed.xml"))
+        .expect("Subscription was not pulled");
+    assert_eq!(rust.folder, Some("Tech".to_string()));
+
+    let first = &select_all_entries(conn, &rust.id)
+        .await
+        .expect("Failed to select entries")[0];
+    assert_eq!(first.title, Some("First Post".to_string()));
+    assert_eq!((first.read, first.starred), (Some(false), Some(false)));
+
+    let cats = feeds.iter().find(|feed| feed.id != rust.id).unwrap();
+    let second = &select_all_entries(conn, &cats.id)
+        .await
+        .expect("Failed to select entries")[0];
+    assert_eq!((second.read, second.starred), (Some(true), Some(true)));
+
+    // Changes made offline wait in the queue until a push goes through
+    queue_change(conn, &first.id, SyncAction::Unread)
+        .await
+        .expect("Failed to queue change");
+    queue_change(conn, &first.id, SyncAction::Read)
+        .await
+        .expect("Failed to queue change");
+    assert_eq!(select_sync_queue(conn).await.unwrap().len(), 1);
+
+    let offline = mock_server(|request| match request.contains("/accounts/ClientLogin") {
+        true => b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\nAuth=abc123".to_vec(),
+        false => b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\r\n".to_vec(),
+    })
+    .await;
+    let offline_settings = SyncSettings::GoogleReader {
+        url: format!("{offline}/api"),
+        username: "me".to_string(),
+        password: Secret::Plain("secret".to_string()),
+    };
+    let mut offline_backend = SyncBackend::connect(&offline_settings, client)
+        .await
+        .expect("Failed to log in");
+    assert!(sync(conn, &mut offline_backend, &[]).await.is_err());
+    assert_eq!(select_sync_queue(conn).await.unwrap().len(), 1);
+
+    let report = sync(conn, &mut backend, &[]).await.expect("Failed to sync");
+    assert_eq!((report.pushed, report.entries), (1, 0));
+    assert!(select_sync_queue(conn).await.unwrap().is_empty());
+
+    assert_eq!(
+        short_id("tag:google.com,2005:reader/item/000000000000001f"),
+        "31"
+    );
+    assert_eq!(
+        long_id("31"),
+        "tag:google.com,2005:reader/item/000000000000001f"
+    );
+}
+
+#[tokio::test]
+async fn synced_entries_stay_in_their_feed() {
+    let conn = &mut connect(get_test_database_url())
+        .await
+        .expect("Failed to connect to database");
+
+    // Fetched directly, not on the sync server
+    let xml = "<feed xmlns=\"http://www.w3.org/2005/Atom\"><title>Local Only</title>\
+        <id>urn:local-only</id><updated>2024-05-01T12:00:00Z</updated>\
+        <entry><title>Weekly Update</title><id>urn:local-only:weekly</id>\
+        <updated>2024-05-01T12:00:00Z</updated></entry></feed>";
+    let feed = feed_rs::parser::parse(xml.as_bytes()).expect("Failed to parse feed");
+    let (local_id, _) = insert_feed(conn, feed, &[])
+        .await
+        .expect("Failed to insert feed");
+
+    let base = mock_server(|request| {
+        let line = request.lines().next().unwrap_or_default();
+        let body = if line.contains("/accounts/ClientLogin") {
+            "Auth=abc123\n"
+        } else if line.contains("/subscription/list") {
+            r#"{"subscriptions": [{"id": "feed/1", "title": "Remote", "url": "https://remote.example.com/rss"}]}"#
+        } else if line.contains("/stream/contents/") {
+            r#"{"items": [{"id": "tag:google.com,2005:reader/item/0000000000000001",
+                "title": "Weekly Update", "origin": {"streamId": "feed/1"}}]}"#
+        } else if line.contains("/stream/items/ids") && line.contains("starred") {
+            r#"{"itemRefs": [{"id": "1"}]}"#
+        } else {
+            r#"{"itemRefs": []}"#
+        };
+
+        format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}", body.len()).into_bytes()
+    })
+    .await;
+
+    let settings = SyncSettings::GoogleReader {
+        url: format!("{base}/api"),
+        username: "me".to_string(),
+        password: Secret::Plain("secret".to_string()),
+    };
+    let client = build_client(&HttpSettings::default()).expect("Failed to build client");
+    let mut backend = SyncBackend::connect(&settings, client)
+        .await
+        .expect("Failed to log in");
+
+    let report = sync(conn, &mut backend, &[]).await.expect("Failed to sync");
+    assert_eq!(report.entries, 1);
+
+    // The remote state of an entry with the same title isn't written onto the local one
+    let local = &select_all_entries(conn, &local_id).await.unwrap()[0];
+    assert_eq!((local.read, local.starred), (Some(false), Some(false)));
+
+    let remote = select_all_feeds(conn)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|feed| feed.id != local_id)
+        .expect("Subscription was not pulled");
+    let synced = &select_all_entries(conn, &remote.id).await.unwrap()[0];
+    assert_eq!(synced.title, Some("Weekly Update".to_string()));
+    assert_eq!((synced.read, synced.starred), (Some(true), Some(true)));
+}
+
+#[tokio::test]
+async fn linked_feeds_outlive_their_subscription() {
+    let conn = &mut connect(get_test_database_url())
+        .await
+        .expect("Failed to connect to database");
+
+    let xml = "<feed xmlns=\"http://www.w3.org/2005/Atom\"><title>Subscribed Before</title>\
+        <id>urn:subscribed-before</id><updated>2024-05-01T12:00:00Z</updated></feed>";
+    let feed = feed_rs::parser::parse(xml.as_bytes()).expect("Failed to parse feed");
+    let (linked_id, _) = insert_feed(conn, feed, &[])
+        .await
+        .expect("Failed to insert feed");
+    update_feed_url(
+        conn,
+        &linked_id,
+        "https://linked.example.com/rss".to_string(),
+    )
+    .await
+    .expect("Failed to update feed url");
+
+    let subscribed = mock_server(|request| {
+        let body = if request.contains("/accounts/ClientLogin") {
+            "Auth=abc123\n"
+        } else if request.contains("/subscription/list") {
+            r#"{"subscriptions": [
+                {"id": "feed/1", "title": "Linked", "url": "https://linked.example.com/rss"},
+                {"id": "feed/2", "title": "Created", "url": "https://created.example.com/rss"}
+            ]}"#
+        } else {
+            "{}"
+        };
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .into_bytes()
+    })
+    .await;
+    let unsubscribed = mock_server(|request| {
+        let body = if request.contains("/accounts/ClientLogin") {
+            "Auth=abc123\n"
+        } else if request.contains("/subscription/list") {
+            r#"{"subscriptions": []}"#
+        } else {
+            "{}"
+        };
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .into_bytes()
+    })
+    .await;
+
+    let client = build_client(&HttpSettings::default()).expect("Failed to build client");
+    for base in [subscribed, unsubscribed] {
+        let settings = SyncSettings::GoogleReader {
+            url: format!("{base}/api"),
+            username: "me".to_string(),
+            password: Secret::Plain("secret".to_string()),
+        };
+        let mut backend = SyncBackend::connect(&settings, client.clone())
+            .await
+            .expect("Failed to log in");
+        sync(conn, &mut backend, &[]).await.expect("Failed to sync");
+    }
+
+    // Only the feed the sync created goes with the subscription
+    let feeds = select_all_feeds(conn).await.unwrap();
+    assert!(feeds.iter().any(|feed| feed.id == linked_id));
+    assert!(!feeds
+        .iter()
+        .any(|feed| feed.url.as_deref() == Some("https://created.example.com/rss")));
+    assert!(select_sync_feeds(conn).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn entries_are_synced_with_miniflux() {
+    init_logger();
+    let conn = &mut connect(get_test_database_url())
+        .await
+        .expect("Failed to connect to database");
+
+    let base = mock_server(|request| {
+        let line = request.lines().next().unwrap_or_default();
+        let body = if !request.to_lowercase().contains("x-auth-token: t0ken") {
+            return b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\n\r\n".to_vec();
+        } else if line.starts_with("GET /v1/feeds") {
+            r#"[
+                {"id": 1, "title": "Rust Blog", "feed_url": "https://blog.rust-lang.org/feed.xml",
+                 "category": {"id": 7, "title": "Tech"}},
+                {"id": 2, "title": "Cats", "feed_url": "https://cats.example.com/rss"}
+            ]"#
+            .to_string()
+        } else if line.contains("status=unread") {
+            r#"{"total": 1, "entries": [{"id": 31, "feed_id": 1, "title": "First Post"}]}"#
+                .to_string()
+        } else if line.contains("starred=true") {
+            r#"{"total": 1, "entries": [{"id": 32, "feed_id": 2, "title": "Second Post"}]}"#
+                .to_string()
+        } else if line.starts_with("GET /v1/entries/31") {
+            r#"{"id": 31, "feed_id": 1, "title": "First Post", "starred": false}"#.to_string()
+        } else if line.starts_with("GET /v1/entries") {
+            r#"{"total": 2, "entries": [
+                {"id": 32, "feed_id": 2, "title": "Second Post",
+                 "url": "https://cats.example.com/second", "content": ""},
+                {"id": 31, "feed_id": 1, "title": "First Post",
+                 "url": "https://blog.rust-lang.org/first", "content": "<p>Hello</p>",
+                 "published_at": "2024-05-01T12:00:00Z"}
+            ]}"#
+            .to_string()
+        } else if line.starts_with("PUT /v1/entries") {
+            String::new()
+        } else {
+            return b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec();
+        };
+
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .into_bytes()
+    })
+    .await;
+
+    let client = build_client(&HttpSettings::default()).expect("Failed to build client");
+    let wrong_token = SyncSettings::Miniflux {
+        url: base.clone(),
+        token: Secret::Plain("wrong".to_string()),
+    };
+    let mut backend = SyncBackend::connect(&wrong_token, client.clone())
+        .await
+        .expect("Failed to connect");
+    assert!(matches!(
+        sync(conn, &mut backend, &[]).await,
+        Err(Error::Unauthorized(_))
+    ));
+
+    let settings = SyncSettings::Miniflux {
+        url: format!("{base}/"),
+        token: Secret::Plain("t0ken".to_string()),
+    };
+    let mut backend = SyncBackend::connect(&settings, client)
+        .await
+        .expect("Failed to connect");
+
+    let report = sync(conn, &mut backend, &[]).await.expect("Failed to sync");
+    assert_eq!((report.pushed, report.feeds, report.entries), (0, 2, 2));
+
+    let feeds = select_all_feeds(conn)
+        .await
+        .expect("Failed to select feeds");
+    let rust = feeds
+        .iter()
+        .find(|feed| feed.url.as_deref() == Some("https://blog.rust-lang.org/feed.xml"))
+        .expect("Subscription was not pulled");
+    assert_eq!(rust.folder, Some("Tech".to_string()));
+
+    let first = &select_all_entries(conn, &rust.id)
+        .await
+        .expect("Failed to select entries")[0];
+    assert_eq!(first.title, Some("First Post".to_string()));
+    assert_eq!((first.read, first.starred), (Some(false), Some(false)));
+
+    let cats = feeds.iter().find(|feed| feed.id != rust.id).unwrap();
+    let second = &select_all_entries(conn, &cats.id)
+        .await
+        .expect("Failed to select entries")[0];
+    assert_eq!((second.read, second.starred), (Some(true), Some(true)));
+
+    queue_change(conn, &first.id, SyncAction::Read)
+        .await
+        .expect("Failed to queue change");
+    queue_change(conn, &first.id, SyncAction::Star)
+        .await
+        .expect("Failed to queue change");
+
+    let report = sync(conn, &mut backend, &[]).await.expect("Failed to sync");
+    assert_eq!((report.pushed, report.entries), (2, 0));
+    assert!(select_sync_queue(conn).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn hooks_run_for_new_entries() {
+    let conn = &mut connect(get_test_database_url())
+        .await
+        .expect("Failed to connect to database");
+
+    let channel = |items: &str| {
+        let rss = format!(
+            "<rss version=\"2.0\"><channel><title>Hooked Feed</title>{items}</channel></rss>"
+        );
+        feed_rs::parser::parse(rss.as_bytes()).expect("Failed to parse feed")
+    };
+
+    let (feed_id, inserted) = insert_feed(conn, channel("<item><title>Old</title></item>"), &[])
+        .await
+        .expect("Failed to insert feed");
+    assert_eq!(inserted.len(), 1);
+
+    let url = "https://hooked.example.com/feed.xml".to_string();
+    let properties = FeedProperties {
+        title: None,
+        url: url.clone(),
+        notes: None,
+    };
+    update_feed_properties(conn, &feed_id, &properties)
+        .await
+        .expect("Failed to update feed properties");
+
+    let dir = std::env::temp_dir();
+    let output = |name: &str| dir.join(format!("crabfeed-hook-{name}.json"));
+    let all = format!("cat > {}", output("all").display());
+    let feed = format!("cat > {}", output("feed").display());
+    let release = format!("cat > {}", output("release").display());
+    for name in ["all", "feed", "release"] {
+        let _ = std::fs::remove_file(output(name));
+    }
+
+    let mut config = Settings::default();
+    config.hooks = vec!["echo broken >&2; exit 3".to_string(), all.clone()];
+    config.feeds.push(FeedSettings {
+        url,
+        hooks: vec![feed.clone(), all.clone()],
+        ..Default::default()
+    });
+    config.rules.push(Rule {
+        title: Some("^Release".parse().unwrap()),
+        actions: vec![RuleAction::Hook(release.clone())],
+        ..Default::default()
+    });
+
+    let items =
+        "<item><title>Release 1.0</title><link>https://hooked.example.com/1.0</link></item>\
+        <item><title>Other News</title></item>\
+        <item><title>Old</title></item>";
+    let (_, inserted) = insert_feed(conn, channel(items), &config.rules)
+        .await
+        .expect("Failed to insert feed");
+    assert_eq!(inserted.len(), 2);
+
+    let hooks = prepare_hooks(conn, &config, &inserted)
+        .await
+        .expect("Failed to prepare hooks");
+    let commands: Vec<&str> = hooks.iter().map(|hook| hook.command.as_str()).collect();
+    assert_eq!(
+        commands,
+        vec!["echo broken >&2; exit 3", &all, &feed, &release]
+    );
+
+    // The failing hook comes first and the rest still run
+    run_hooks(hooks).await;
+
+    let read = |name: &str| -> serde_json::Value {
+        let json = std::fs::read_to_string(output(name)).expect("Hook did not run");
+        serde_json::from_str(&json).expect("Hook got invalid JSON")
+    };
+
+    let payload = read("all");
+    assert_eq!(payload["feed"]["title"], "Hooked Feed");
+    assert_eq!(payload["entries"].as_array().unwrap().len(), 2);
+    assert_eq!(read("feed")["entries"], payload["entries"]);
+
+    let release = read("release");
+    let entries = release["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["title"], "Release 1.0");
+    assert_eq!(entries[0]["link"], "https://hooked.example.com/1.0");
+}
+
+#[tokio::test]
+async fn statistics_summarize_feeds() {
+    let conn = &mut connect(get_test_database_url())
+        .await
+        .expect("Failed to connect to database");
+
+    let atom = |title: &str, entries: &[(&str, &str)]| {
+        let entries: String = entries
+            .iter()
+            .map(|(title, updated)| {
+                format!(
+                    "<entry><title>{title}</title><id>urn:{title}</id><updated>{updated}</updated></entry>"
+                )
+            })
+            .collect();
+        let xml = format!(
+            "<feed xmlns=\"http://www.w3.org/2005/Atom\"><title>{title}</title><id>urn:{title}</id>\
+            <updated>2024-06-01T00:00:00Z</updated>{entries}</feed>"
+        );
+        feed_rs::parser::parse(xml.as_bytes()).expect("Failed to parse feed")
+    };
+
+    let busy = atom(
+        "Busy",
+        &[
+            ("Busy One", "2024-05-31T12:00:00Z"),
+            ("Busy Two", "2024-05-30T12:00:00Z"),
+            ("Busy Three", "2024-05-22T12:00:00Z"),
+        ],
+    );
+    let quiet = atom("Quiet", &[("Quiet One", "2024-02-01T12:00:00Z")]);
+
+    let (busy_id, _) = insert_feed(conn, busy, &[])
+        .await
+        .expect("Failed to insert feed");
+    insert_feed(conn, quiet, &[])
+        .await
+        .expect("Failed to insert feed");
+
+    let entry_id = select_all_entries(conn, &busy_id)
+        .await
+        .expect("Failed to select entries")[0]
+        .id;
+    mark_entry_read(conn, &entry_id)
+        .await
+        .expect("Failed to mark entry read");
+
+    let now = DateTime::parse_from_rfc3339("2024-06-01T12:00:00Z")
+        .unwrap()
+        .naive_utc();
+    let stats = statistics(conn, now, 30)
+        .await
+        .expect("Failed to take statistics");
+
+    assert_eq!(stats.feeds.len(), 2);
+    assert_eq!(stats.entries(), 4);
+    assert!(stats.database_size > 0);
+
+    let busy = &stats.feeds[0];
+    assert_eq!(busy.title, "Busy");
+    assert_eq!((busy.entries, busy.read), (3, 1));
+    assert_eq!(busy.weekly.len(), WEEKS);
+    assert_eq!(busy.weekly[WEEKS - 1], 2);
+    assert_eq!(busy.weekly[WEEKS - 2], 1);
+    assert_eq!(busy.recent(), 3);
+
+    assert_eq!(stats.weekly()[WEEKS - 1], 2);
+    assert_eq!(stats.feeds[1].recent(), 0);
+
+    let most_active: Vec<&str> = stats
+        .most_active(5)
+        .iter()
+        .map(|feed| feed.title.as_str())
+        .collect();
+    assert_eq!(most_active, vec!["Busy"]);
+
+    let stale: Vec<&str> = stats
+        .stale()
+        .iter()
+        .map(|feed| feed.title.as_str())
+        .collect();
+    assert_eq!(stale, vec!["Quiet"]);
+
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(1536), "1.5 KiB");
+    assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB");
+}
+
+#[tokio::test]
+async fn duplicates_are_found_across_feeds() {
+    assert_eq!(
+        canonical_link("https://www.example.com/post/?utm_source=feed&page=2#top"),
+        Some("example.com/post?page=2".to_string())
+    );
+    assert_eq!(
+        canonical_link("http://example.com/post"),
+        Some("example.com/post".to_string())
+    );
+    assert_eq!(canonical_link(" "), None);
+
+    let db_url = get_test_database_url();
+    let conn = &mut connect(db_url.clone())
+        .await
+        .expect("Failed to connect to database");
+
+    let atom = |title: &str, entries: &[(&str, &str, &str)]| {
+        let entries: String = entries
+            .iter()
+            .map(|(title, id, link)| {
+                format!(
+                    "<entry><title>{title}</title><id>{id}</id><link href=\"{link}\"/>\
+                    <updated>2024-05-01T12:00:00Z</updated></entry>"
+                )
+            })
+            .collect();
+        let xml = format!(
+            "<feed xmlns=\"http://www.w3.org/2005/Atom\"><title>{title}</title><id>urn:{title}</id>\
+            <updated>2024-05-01T12:00:00Z</updated>{entries}</feed>"
+        );
+        feed_rs::parser::parse(xml.as_bytes()).expect("Failed to parse feed")
+    };
+
+    let original = atom(
+        "Original",
+        &[
+            ("Launch", "1", "https://example.com/launch?utm_medium=rss"),
+            (
+                "Update",
+                "tag:example.com,2024:update",
+                "https://example.com/update",
+            ),
+            ("Notes", "2", "https://example.com/notes"),
+            ("Launch, again", "3", "https://example.com/launch"),
+        ],
+    );
+    let mirror = atom(
+        "Mirror",
+        &[
+            ("Mirrored: Launch", "9", "http://www.example.com/launch/"),
+            (
+                "Mirrored: Update",
+                "tag:example.com,2024:update",
+                "https://mirror.example.org/u",
+            ),
+            ("Mirrored: Notes", "2", "https://mirror.example.org/notes"),
+            // Titles only have to differ within a feed
+            ("Notes", "10", "https://mirror.example.org/other-notes"),
+        ],
+    );
+
+    let (original_id, _) = insert_feed(conn, original, &[])
+        .await
+        .expect("Failed to insert feed");
+    let (mirror_id, _) = insert_feed(conn, mirror, &[])
+        .await
+        .expect("Failed to insert feed");
+
+    let find = |entries: &[crabfeed::prelude::Entry], title: &str| {
+        entries
+            .iter()
+            .find(|entry| entry.title.as_deref() == Some(title))
+            .expect("Entry is missing")
+            .id
+    };
+    let originals = select_all_entries(conn, &original_id).await.unwrap();
+    let mirrors = select_all_entries(conn, &mirror_id).await.unwrap();
+
+    let launch = find(&originals, "Launch");
+    let update = find(&originals, "Update");
+    let notes = find(&originals, "Notes");
+
+    // Matched by link and by GUID, but short GUIDs are only unique within a feed.
+    // The re-post in the same feed is not a copy.
+    assert_eq!(
+        select_duplicate_ids(conn, &launch).await.unwrap(),
+        vec![find(&mirrors, "Mirrored: Launch")]
+    );
+    assert_eq!(
+        select_duplicate_ids(conn, &find(&originals, "Launch, again"))
+            .await
+            .unwrap(),
+        vec![find(&mirrors, "Mirrored: Launch")]
+    );
+    assert_eq!(mirrors.len(), 4);
+    assert_eq!(
+        select_duplicate_ids(conn, &update).await.unwrap(),
+        vec![find(&mirrors, "Mirrored: Update")]
+    );
+    assert!(select_duplicate_ids(conn, &notes).await.unwrap().is_empty());
+
+    let everything: Vec<_> = originals.iter().chain(mirrors.iter()).cloned().collect();
+    assert_eq!(collapse_duplicates(everything).len(), 5);
+
+    let (sender, _receiver) = tokio::sync::mpsc::channel(32);
+    data::handle_event(
+        &get_test_config(&db_url),
+        DataEvent::ReadEntry(launch),
+        sender,
+    )
+    .await
+    .expect("Failed to handle ReadEntry event");
+
+    let mirrored = select_entry(conn, &find(&mirrors, "Mirrored: Launch"))
+        .await
+        .expect("Failed to select entry");
+    assert_eq!(mirrored.read, Some(true));
+    let repost = select_entry(conn, &find(&originals, "Launch, again"))
+        .await
+        .expect("Failed to select entry");
+    assert_ne!(repost.read, Some(true));
+    let unrelated = select_entry(conn, &find(&mirrors, "Mirrored: Notes"))
+        .await
+        .expect("Failed to select entry");
+    assert_ne!(unrelated.read, Some(true));
+}
+
+#[tokio::test]
+async fn ui_state_is_restored() {
+    let entry = |id: i64, feed_id: i64| EntryData {
+        id,
+        feed_id,
+        title: format!("Entry {id}"),
+        ..Default::default()
+    };
+    let feeds = vec![
+        FeedData {
+            id: 1,
+            title: "First".to_string(),
+            ..Default::default()
+        },
+        FeedData {
+            id: 2,
+            title: "Second".to_string(),
+            ..Default::default()
+        },
+    ];
+    let entries = vec![vec![entry(10, 1)], vec![entry(20, 2), entry(21, 2)]];
+
+    let saved = UiState {
+        routes: vec![
+            Route::default(),
+            Route::new(RouteId::Home, ActiveBlock::Entries),
+            Route::new(RouteId::Entry, ActiveBlock::Entry),
+        ],
+        feed_id: Some(2),
+        entry_id: Some(21),
+        line_index: 7,
+        unread_only: true,
+    };
+
+    // Nothing is saved before the last state was restored
+    let mut ui = Ui::new(Settings::default());
+    assert_eq!(ui.state(), None);
+
+    ui.update_feeds(feeds.clone());
+    ui.update_entries(entries.clone());
+    ui.restore_state(saved.clone());
+    assert_eq!(ui.state(), Some(saved.clone()));
+    assert_eq!(
+        ui.get_current_route(),
+        Some(&Route::new(RouteId::Entry, ActiveBlock::Entry))
+    );
+
+    // Without its entry the reader lands on the feed's entries
+    let mut ui = Ui::new(Settings::default());
+    ui.update_feeds(feeds);
+    ui.update_entries(vec![entries[0].clone(), vec![entry(20, 2)]]);
+    ui.restore_state(saved.clone());
+    assert_eq!(
+        ui.get_current_route(),
+        Some(&Route::new(RouteId::Home, ActiveBlock::Entries))
+    );
+    assert_eq!(ui.state().and_then(|state| state.feed_id), Some(2));
+
+    // The state is kept in the database between sessions
+    let db_url = get_test_database_url();
+    let (sender, mut receiver) = tokio::sync::mpsc::channel(32);
+    data::handle_event(
+        &get_test_config(&db_url),
+        DataEvent::LoadUiState,
+        sender.clone(),
+    )
+    .await
+    .expect("Failed to handle LoadUiState event");
+    match receiver.try_recv() {
+        Ok(AppEvent::UiState(state)) => assert_eq!(state, UiState::default()),
+        e => panic!("Unexpected event received, {:?}", e),
+    }
+    assert!(matches!(receiver.try_recv(), Ok(AppEvent::Complete)));
+
+    data::handle_event(
+        &get_test_config(&db_url),
+        DataEvent::SaveUiState(saved.clone()),
+        sender.clone(),
+    )
+    .await
+    .expect("Failed to handle SaveUiState event");
+    assert!(matches!(receiver.try_recv(), Ok(AppEvent::Complete)));
+
+    data::handle_event(&get_test_config(&db_url), DataEvent::LoadUiState, sender)
+        .await
+        .expect("Failed to handle LoadUiState event");
+    match receiver.try_recv() {
+        Ok(AppEvent::UiState(state)) => assert_eq!(state, saved),
+        e => panic!("Unexpected event received, {:?}", e),
+    }
+}
+
+#[tokio::test]
+async fn reading_position_is_saved() {
+    assert_eq!(reading_progress(0, 10, 40), 25);
+    assert_eq!(reading_progress(15, 10, 40), 62);
+    assert_eq!(reading_progress(35, 10, 40), 100);
+    assert_eq!(reading_progress(0, 10, 0), 100);
+
+    let db_url = get_test_database_url();
+    let conn = &mut connect(db_url.clone())
+        .await
+        .expect("Failed to connect to database");
+
+    let xml = "<feed xmlns=\"http://www.w3.org/2005/Atom\"><title>Long Reads</title>\
+        <id>urn:long-reads</id><updated>2024-05-01T12:00:00Z</updated>\
+        <entry><title>Essay</title><id>urn:long-reads:essay</id>\
+        <updated>2024-05-01T12:00:00Z</updated></entry></feed>";
+    let feed = feed_rs::parser::parse(xml.as_bytes()).expect("Failed to parse feed");
+    let (feed_id, inserted) = insert_feed(conn, feed, &[])
+        .await
+        .expect("Failed to insert feed");
+    let entry_id = inserted[0].id;
+
+    let entry = select_entry(conn, &entry_id).await.unwrap();
+    assert_eq!(EntryData::from(entry).line_index, 0);
+
+    let (sender, mut receiver) = tokio::sync::mpsc::channel(32);
+    data::handle_event(
+        &get_test_config(&db_url),
+        DataEvent::SavePosition(entry_id, 42),
+        sender,
+    )
+    .await
+    .expect("Failed to handle SavePosition event");
+    assert!(matches!(receiver.try_recv(), Ok(AppEvent::Complete)));
+
+    let entries = select_all_entries(conn, &feed_id).await.unwrap();
+    assert_eq!(entries[0].line_index, Some(42));
+    assert_eq!(EntryData::from(entries[0].clone()).line_index, 42);
+}
+
+#[test]
+fn feed_deletion_can_be_undone() {
+    let feed = |id: i64, title: &str| FeedData {
+        id,
+        title: title.to_string(),
+        ..Default::default()
+    };
+    let feeds = vec![feed(1, "First"), feed(2, "Second"), feed(3, "Third")];
+    let entries = vec![vec![], vec![], vec![]];
+
+    let mut ui = Ui::new(Settings::default());
+    ui.update_feeds(feeds.clone());
+    ui.update_entries(entries.clone());
+
+    // Deleting another feed ends the undo window of the one before
+    assert_eq!(ui.delete_feed(1), None);
+    assert_eq!(ui.delete_feed(2), Some(1));
+    assert!(!ui.go_to_feed("Second"));
+
+    assert!(ui.undo_delete());
+    assert!(!ui.undo_delete());
+    assert!(ui.go_to_feed("Second"));
+
+    // Refreshed data doesn't bring a deleted feed back
+    assert_eq!(ui.delete_feed(3), None);
+    ui.update_feeds(feeds);
+    ui.update_entries(entries);
+    ui.hide_deleted_feed();
+    assert!(!ui.go_to_feed("Third"));
+
+    assert_eq!(ui.expired_feed(), None);
+    assert_eq!(ui.take_deleted_feed(), Some(3));
+    assert_eq!(ui.take_deleted_feed(), None);
+}
+
+#[tokio::test]
+async fn clicks_land_where_they_were_drawn() {
+    let feeds = vec![FeedData {
+        id: 1,
+        title: "Only".to_string(),
+        ..Default::default()
+    }];
+    let entries: Vec<EntryData> = (1..=30)
+        .map(|id| EntryData {
+            id,
+            feed_id: 1,
+            title: format!("Entry {id}"),
+            ..Default::default()
+        })
+        .collect();
+
+    let mut ui = Ui::new(Settings::default());
+    ui.update_feeds(feeds);
+    ui.update_entries(vec![entries]);
+    // Starts scrolled down, so the clicked row is not the row's index in the list
+    ui.restore_state(UiState {
+        routes: vec![
+            Route::default(),
+            Route::new(RouteId::Home, ActiveBlock::Entries),
+        ],
+        feed_id: Some(1),
+        entry_id: Some(2),
+        line_index: 0,
+        unread_only: false,
+    });
+
+    let area = Rect::new(0, 0, 80, 20);
+    let mut buf = Buffer::empty(area);
+    (&mut ui).render(area, &mut buf);
+
+    let (column, row) = find_text(&buf, "Entry 5 ").expect("Entry 5 is not drawn");
+    let click = |column, row| MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    };
+    assert!(ui.handle_mouse_event(click(column, row)).is_none());
+    assert_eq!(ui.state().and_then(|state| state.entry_id), Some(5));
+
+    // A click inside a popup is left to it, one outside closes it
+    ui.open_finder();
+    let mut buf = Buffer::empty(area);
+    (&mut ui).render(area, &mut buf);
+    let (column, row) = find_text(&buf, "Find (").expect("Finder is not drawn");
+    ui.handle_mouse_event(click(column, row + 1));
+    let mut buf = Buffer::empty(area);
+    (&mut ui).render(area, &mut buf);
+    assert!(find_text(&buf, "Find (").is_some());
+
+    assert!(ui.handle_mouse_event(click(0, 0)).is_none());
+    let mut buf = Buffer::empty(area);
+    (&mut ui).render(area, &mut buf);
+    assert!(find_text(&buf, "Find (").is_none());
+    assert_eq!(ui.state().and_then(|state| state.entry_id), Some(5));
+}
+
+#[test]
+fn messages_are_kept_in_history() {
+    let mut ui = Ui::new(Settings::default());
+    assert!(ui.messages().is_empty());
+
+    ui.notify(Severity::Info, "Exported to feed.epub".to_string());
+    ui.notify(Severity::Error, "No such feed".to_string());
+
+    let messages: Vec<(Severity, &str)> = ui
+        .messages()
+        .iter()
+        .map(|message| (message.severity, message.text.as_str()))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            (Severity::Info, "Exported to feed.epub"),
+            (Severity::Error, "No such feed")
+        ]
+    );
+}
+
+#[test]
+fn log_level_is_read_from_the_cli_first() {
+    let args = ["serve", "--log-level", "debug", "--address", "0.0.0.0:8080"].map(String::from);
+    let (level, rest) = take_log_level(args).expect("Failed to take log level");
+    assert_eq!(level, Some(LevelFilter::Debug));
+    assert_eq!(
+        parse_args(rest).ok(),
+        Some(Command::Serve("0.0.0.0:8080".parse().unwrap()))
+    );
+    assert!(take_log_level(["--log-level", "loud"].map(String::from)).is_err());
+    assert!(take_log_level(["--log-level"].map(String::from)).is_err());
+
+    // The command line wins over the settings
+    let config = Settings {
+        log_level: Some("warn".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(log_level(None, &config).unwrap(), LevelFilter::Warn);
+    assert_eq!(
+        log_level(Some(LevelFilter::Trace), &config).unwrap(),
+        LevelFilter::Trace
+    );
+    assert_eq!(
+        log_level(None, &Settings::default()).unwrap(),
+        LevelFilter::Info
+    );
+}
+
+#[test]
+fn logs_are_rotated() {
+    let dir = std::env::temp_dir().join(format!(
+        "crabfeed-logs-{}",
+        Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    create_dir_all(&dir).expect("Failed to create log folder");
+    let path = dir.join("crabfeed.log");
+
+    let mut file = RotatingFile::open(path.clone(), 100, 2).expect("Failed to open log file");
+    for line in 0..12 {
+        writeln!(file, "{line:0>39}").expect("Failed to write log line");
+    }
+    file.flush().unwrap();
+
+    // 40 byte lines, two to a file, the oldest ones are dropped
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap_or_default();
+    assert_eq!(read("crabfeed.log").lines().count(), 2);
+    assert!(read("crabfeed.log").ends_with(&format!("{:0>39}\n", 11)));
+    assert!(read("crabfeed.log.1").starts_with(&format!("{:0>39}\n", 8)));
+    assert!(read("crabfeed.log.2").starts_with(&format!("{:0>39}\n", 6)));
+    assert!(!dir.join("crabfeed.log.3").exists());
+
+    std::fs::remove_dir_all(dir).expect("Failed to remove log folder");
+}
+
 #[test]
 fn configuration_is_found() {
     let config = get_configuration().unwrap();
@@ -387,6 +1872,361 @@ fn hex_code_string_returns_rgb() {
     assert_eq!(color, Color::Rgb(0, 255, 0));
 }
 
+#[test]
+fn article_content_is_extracted() {
+    let html = r#"
+        <html>
+            <body>
+                <nav><p>Home</p><p>About</p></nav>
+                <div class="post">
+                    <h1>Title</h1>
+                    <p>The first paragraph of the article.</p>
+                    <p>A second paragraph with <b>bold</b> text.</p>
+                    <script>tracking();</script>
+                </div>
+                <footer><p>Copyright</p></footer>
+            </body>
+        </html>
+    "#;
+
+    let article = extract_article(html).expect("Failed to extract article");
+
+    assert!(article.contains("<h1>Title</h1>"));
+    assert!(article.contains("<b>bold</b>"));
+    assert!(!article.contains("tracking"));
+    assert!(!article.contains("Copyright"));
+}
+
+#[tokio::test]
+async fn article_is_cached_apart_from_content() {
+    let db_url = get_test_database_url();
+    let conn = &mut connect(db_url)
+        .await
+        .expect("Failed to connect to database");
+
+    // Content with a src used to be taken for a cached article
+    let xml = "<feed xmlns=\"http://www.w3.org/2005/Atom\"><title>Linked Content</title>\
+        <id>urn:linked-content</id><updated>2024-05-01T12:00:00Z</updated>\
+        <entry><title>Linked</title><id>urn:linked-content:linked</id>\
+        <updated>2024-05-01T12:00:00Z</updated>\
+        <content type=\"text/html\" src=\"https://example.com/linked\"/></entry></feed>";
+    let feed = feed_rs::parser::parse(xml.as_bytes()).expect("Failed to parse feed");
+    let (_, inserted) = insert_feed(conn, feed, &[])
+        .await
+        .expect("Failed to insert feed");
+    let entry_id = inserted[0].id;
+    let content_id = select_entry(conn, &entry_id).await.unwrap().content_id;
+
+    assert_eq!(select_article(conn, &entry_id).await.unwrap(), None);
+
+    insert_article(
+        conn,
+        &entry_id,
+        "https://example.com/linked".to_string(),
+        "<p>Full text</p>".to_string(),
+    )
+    .await
+    .expect("Failed to insert article");
+
+    let article = select_article(conn, &entry_id).await.unwrap().unwrap();
+    assert_eq!(article.body, Some("<p>Full text</p>".to_string()));
+    assert_eq!(
+        select_entry(conn, &entry_id).await.unwrap().content_id,
+        content_id
+    );
+}
+
+#[test]
+fn rules_match_new_entries() {
+    let rules = vec![
+        Rule {
+            feed: Some("busy feed".to_string()),
+            title: Some("(?i)sponsored".parse().unwrap()),
+            actions: vec![RuleAction::MarkRead, RuleAction::Hide],
+            ..Default::default()
+        },
+        Rule {
+            title: Some("^Release".parse().unwrap()),
+            actions: vec![RuleAction::Tag("releases".to_string())],
+            ..Default::default()
+        },
+        Rule {
+            actions: vec![RuleAction::Delete],
+            ..Default::default()
+        },
+    ];
+
+    let feed = RuleFeed {
+        title: Some("Busy Feed"),
+        urls: vec!["https://example.com/feed.xml"],
+    };
+
+    let mut entry = feed_rs::model::Entry::default();
+    entry.title = Some(feed_rs::model::Text {
+        content_type: mime::TEXT_PLAIN,
+        src: None,
+        content: "SPONSORED: buy things".to_string(),
+    });
+
+    assert_eq!(
+        rule_actions(&rules, &feed, &entry),
+        vec![RuleAction::MarkRead, RuleAction::Hide]
+    );
+
+    entry.title.as_mut().unwrap().content = "Release 1.0".to_string();
+
+    assert_eq!(
+        rule_actions(&rules, &feed, &entry),
+        vec![RuleAction::Tag("releases".to_string())]
+    );
+
+    // A broken pattern is a configuration error rather than a rule that never matches
+    let rule = r#"{"title": "(unclosed", "actions": ["mark_read"]}"#;
+    assert!(serde_json::from_str::<Rule>(rule).is_err());
+}
+
+#[test]
+fn smart_folder_query_is_compiled() {
+    let query: EntryQuery = r#"unread feed:"Rust Blog" title:~async -tag:seen newer:7d"#
+        .parse()
+        .expect("Failed to parse query");
+
+    assert!(query
+        .filter
+        .contains("entry.read IS NULL OR entry.read = FALSE"));
+    assert!(query
+        .filter
+        .contains("COALESCE(feed.custom_title, feed.title) = ? COLLATE NOCASE"));
+    assert!(query.filter.contains("entry.title LIKE ?"));
+    assert!(query.filter.contains("NOT (entry.id IN"));
+    assert!(query.filter.contains("entry.updated >= ?"));
+
+    assert_eq!(query.params[0], QueryParam::Text("Rust Blog".to_string()));
+    assert_eq!(query.params[1], QueryParam::Text("%async%".to_string()));
+    assert_eq!(query.params[2], QueryParam::Text("seen".to_string()));
+    assert!(matches!(query.params[3], QueryParam::Date(_)));
+
+    assert!("newer:soon".parse::<EntryQuery>().is_err());
+    assert!("colour:red".parse::<EntryQuery>().is_err());
+}
+
+#[test]
+fn entries_are_exported() {
+    let entries = vec![ExportEntry {
+        title: "Hello & Welcome".to_string(),
+        feed: "Example Feed".to_string(),
+        author: Some("Ferris".to_string()),
+        link: Some("https://example.com/hello".to_string()),
+        content: "<p>Some <b>bold</b> text&nbsp;here<br></p>".to_string(),
+        ..Default::default()
+    }];
+
+    let markdown = render_markdown(&entries);
+    assert!(markdown.starts_with("# Hello & Welcome"));
+    assert!(markdown.contains("- **Author:** Ferris"));
+    assert!(markdown.contains("- **Source:** https://example.com/hello"));
+
+    let html = render_html("Example Feed", &entries);
+    assert!(html.contains("<h1>Hello &amp; Welcome</h1>"));
+    assert!(html.contains("<b>bold</b>"));
+
+    assert_eq!(
+        to_xhtml("<p>a&nbsp;&amp; b<br></p>"),
+        "<p>a &amp; b<br/></p>"
+    );
+
+    let args = ["export", "--format", "epub", "--feed", "Example Feed"].map(String::from);
+    match parse_args(args).expect("Failed to parse export args") {
+        Command::Export(export) => {
+            assert_eq!(export.format, ExportFormat::Epub);
+            assert_eq!(export.feed, Some("Example Feed".to_string()));
+        }
+        command => panic!("Unexpected command {:?}", command),
+    }
+}
+
+#[test]
+fn planets_are_served_as_atom() {
+    let config = Settings {
+        smart_folders: vec![SmartFolder {
+            name: "Rust & Friends".to_string(),
+            query: "feed:~rust".to_string(),
+        }],
+        ..Default::default()
+    };
+
+    assert_eq!(slug("Rust & Friends"), "rust-friends");
+    assert_eq!(route(&config, "/all.atom"), Some(Planet::All));
+    assert_eq!(
+        route(&config, "/starred.atom?since=1"),
+        Some(Planet::Starred)
+    );
+    assert_eq!(
+        route(&config, "/folder/rust-friends.atom"),
+        Some(Planet::Folder(config.smart_folders[0].clone()))
+    );
+    assert_eq!(route(&config, "/folder/missing.atom"), None);
+
+    let entries = vec![
+        (
+            1,
+            ExportEntry {
+                title: "Hello & Welcome".to_string(),
+                feed: "Example Feed".to_string(),
+                link: Some("https://example.com/hello".to_string()),
+                content: "<p>Some <b>bold</b> text</p>".to_string(),
+                date: chrono::NaiveDate::from_ymd_opt(2024, 5, 1)
+                    .and_then(|date| date.and_hms_opt(12, 0, 0)),
+                ..Default::default()
+            },
+        ),
+        (
+            2,
+            ExportEntry {
+                title: "No Link".to_string(),
+                feed: "Other Feed".to_string(),
+                author: Some("Ferris".to_string()),
+                ..Default::default()
+            },
+        ),
+    ];
+    let atom = render_atom(&Planet::All, "http://localhost/all.atom", &entries);
+
+    let feed = feed_rs::parser::parse(atom.as_bytes()).expect("Failed to parse planet");
+    assert_eq!(feed.title.unwrap().content, "All Subscriptions");
+    assert_eq!(feed.entries.len(), 2);
+    assert_eq!(feed.entries[0].id, "https://example.com/hello");
+    assert_eq!(
+        feed.entries[0].title.as_ref().unwrap().content,
+        "Hello & Welcome"
+    );
+    assert_eq!(
+        feed.entries[0]
+            .content
+            .as_ref()
+            .and_then(|c| c.body.clone()),
+        Some("<p>Some <b>bold</b> text</p>".to_string())
+    );
+    assert_eq!(feed.entries[1].id, "urn:crabfeed:entry:2");
+    assert_eq!(feed.entries[1].authors[0].name, "Ferris");
+
+    let args = ["serve", "--address", "0.0.0.0:8080"].map(String::from);
+    assert_eq!(
+        parse_args(args).ok(),
+        Some(Command::Serve("0.0.0.0:8080".parse().unwrap()))
+    );
+    assert!(parse_args(["serve", "--address", "nowhere"].map(String::from)).is_err());
+}
+
+#[test]
+fn commands_are_parsed_and_completed() {
+    assert_eq!(
+        ":add https://example.com/feed.xml"
+            .parse::<UiCommand>()
+            .ok(),
+        Some(UiCommand::Add("https://example.com/feed.xml".to_string()))
+    );
+    assert_eq!(
+        "set unread-only!".parse::<UiCommand>().ok(),
+        Some(UiCommand::Set(UiOption::UnreadOnly, None))
+    );
+    assert_eq!(
+        "set nounread-only".parse::<UiCommand>().ok(),
+        Some(UiCommand::Set(UiOption::UnreadOnly, Some(false)))
+    );
+    assert_eq!(
+        "export epub".parse::<UiCommand>().ok(),
+        Some(UiCommand::Export(Some(ExportFormat::Epub)))
+    );
+    assert_eq!("open 3".parse::<UiCommand>().ok(), Some(UiCommand::Open(3)));
+    assert!("open 0".parse::<UiCommand>().is_err());
+    assert_eq!(":stats".parse::<UiCommand>().ok(), Some(UiCommand::Stats));
+    assert_eq!(
+        "messages".parse::<UiCommand>().ok(),
+        Some(UiCommand::Messages)
+    );
+    assert_eq!(":logs".parse::<UiCommand>().ok(), Some(UiCommand::Logs));
+    assert!("add".parse::<UiCommand>().is_err());
+    assert!("frobnicate".parse::<UiCommand>().is_err());
+
+    let feed_titles = vec!["Rust Blog".to_string(), "Ratatui News".to_string()];
+    assert_eq!(
+        completions("ma", &feed_titles),
+        vec!["mark-all-read ".to_string()]
+    );
+    assert_eq!(
+        completions("feed r", &feed_titles),
+        vec![
+            "feed Rust Blog".to_string(),
+            "feed Ratatui News".to_string()
+        ]
+    );
+    assert_eq!(
+        completions("feed rus", &feed_titles),
+        vec!["feed Rust Blog".to_string()]
+    );
+}
+
+#[test]
+fn finder_ranks_fuzzy_matches() {
+    assert!(fuzzy_score("rb", "Rust Blog").is_some());
+    assert!(fuzzy_score("blr", "Rust Blog").is_none());
+    assert!(fuzzy_score("rust", "Rust Blog") > fuzzy_score("rust", "Running unsafe tests"));
+
+    let items = vec![
+        FinderItem {
+            target: FinderTarget::Entry(0, 1),
+            label: "Announcing Rust 1.80 · Rust Blog".to_string(),
+        },
+        FinderItem {
+            target: FinderTarget::Feed(0),
+            label: "Rust Blog".to_string(),
+        },
+        FinderItem {
+            target: FinderTarget::Feed(1),
+            label: "Ratatui News".to_string(),
+        },
+    ];
+
+    assert_eq!(rank("rust blog", &items), vec![1, 0]);
+    assert_eq!(rank("", &items), vec![1, 2, 0]);
+}
+
+// Serves each connection with whatever `respond` returns for the raw request, returning
+// the base url of the server
+async fn mock_server(respond: fn(&str) -> Vec<u8>) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind mock server");
+    let base = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = vec![0; 16 * 1024];
+            let read = stream.read(&mut request).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&request[..read]).to_string();
+
+            let _ = stream.write_all(&respond(&request)).await;
+            let _ = stream.shutdown().await;
+        }
+    });
+
+    base
+}
+
+// Where `text` starts in a rendered buffer
+fn find_text(buf: &Buffer, text: &str) -> Option<(u16, u16)> {
+    (buf.area.top()..buf.area.bottom()).find_map(|row| {
+        let line: String = (buf.area.left()..buf.area.right())
+            .map(|column| buf[(column, row)].symbol())
+            .collect();
+        line.find(text).map(|start| {
+            let column = line[..start].chars().count() as u16;
+            (buf.area.left() + column, row)
+        })
+    })
+}
+
 fn init_logger() {
     let _ = env_logger::builder()
         .target(Target::Stdout)
@@ -394,6 +2234,13 @@ fn init_logger() {
         .try_init();
 }
 
+fn get_test_config(database_url: &str) -> Settings {
+    Settings {
+        database_url: database_url.to_string(),
+        ..Settings::default()
+    }
+}
+
 fn get_test_database_url() -> String {
     let curr_dir = current_dir()
         .expect("Coudn't get current directory")